@@ -107,8 +107,13 @@ pub struct IgvmParamBlock {
     /// The port number of the serial port to use for debugging.
     pub debug_serial_port: u16,
 
+    /// The VMPLs allowed to use each SVSM protocol, one nibble per
+    /// protocol, or zero for the default policy, which allows all protocols
+    /// except the debug protocol to every VMPL.
+    pub service_policy: u16,
+
     #[doc(hidden)]
-    pub _reserved: [u16; 3],
+    pub _reserved: [u16; 2],
 
     /// Metadata containing information about the firmware image embedded in the
     /// IGVM file.
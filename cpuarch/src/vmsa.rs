@@ -4,6 +4,8 @@
 //
 // Author: Joerg Roedel <jroedel@suse.de>
 
+use core::fmt;
+
 // AE Exitcodes
 // Table 15-35, AMD64 Architecture Programmer’s Manual, Vol. 2
 #[repr(u64)]
@@ -165,6 +167,8 @@ pub struct VMSA {
     pub reserved_670: [u8; 2448],
 }
 
+const _: () = assert!(core::mem::size_of::<VMSA>() == 4096);
+
 impl Default for VMSA {
     fn default() -> Self {
         VMSA {
@@ -289,3 +293,110 @@ impl VMSA {
         self.efer &= !(1u64 << 12);
     }
 }
+
+impl fmt::Display for VMSASegment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Fields are copied out with braces as the struct is packed.
+        write!(
+            f,
+            "{:#06x} base {:#018x} limit {:#010x} flags {:#06x}",
+            { self.selector },
+            { self.base },
+            { self.limit },
+            { self.flags }
+        )
+    }
+}
+
+impl fmt::Display for VMSA {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Fields are copied out with braces as the struct is packed.
+        writeln!(
+            f,
+            "RIP: {:#018x}  RSP: {:#018x}  RFLAGS: {:#018x}",
+            { self.rip },
+            { self.rsp },
+            { self.rflags }
+        )?;
+        writeln!(
+            f,
+            "RAX: {:#018x}  RBX: {:#018x}  RCX: {:#018x}  RDX: {:#018x}",
+            { self.rax },
+            { self.rbx },
+            { self.rcx },
+            { self.rdx }
+        )?;
+        writeln!(
+            f,
+            "RSI: {:#018x}  RDI: {:#018x}  RBP: {:#018x}  R8:  {:#018x}",
+            { self.rsi },
+            { self.rdi },
+            { self.rbp },
+            { self.r8 }
+        )?;
+        writeln!(
+            f,
+            "R9:  {:#018x}  R10: {:#018x}  R11: {:#018x}  R12: {:#018x}",
+            { self.r9 },
+            { self.r10 },
+            { self.r11 },
+            { self.r12 }
+        )?;
+        writeln!(
+            f,
+            "R13: {:#018x}  R14: {:#018x}  R15: {:#018x}",
+            { self.r13 },
+            { self.r14 },
+            { self.r15 }
+        )?;
+
+        let segments = [
+            ("CS", self.cs),
+            ("SS", self.ss),
+            ("DS", self.ds),
+            ("ES", self.es),
+            ("FS", self.fs),
+            ("GS", self.gs),
+            ("TR", self.tr),
+            ("LDT", self.ldt),
+            ("GDT", self.gdt),
+            ("IDT", self.idt),
+        ];
+        for (name, seg) in segments {
+            writeln!(f, "{:<4} {}", name, seg)?;
+        }
+
+        writeln!(
+            f,
+            "CR0: {:#018x}  CR2: {:#018x}  CR3: {:#018x}  CR4: {:#018x}",
+            { self.cr0 },
+            { self.cr2 },
+            { self.cr3 },
+            { self.cr4 }
+        )?;
+        writeln!(
+            f,
+            "EFER: {:#018x}  XCR0: {:#018x}  CPL: {}  VMPL: {}",
+            { self.efer },
+            { self.xcr0 },
+            { self.cpl },
+            { self.vmpl }
+        )?;
+
+        writeln!(
+            f,
+            "SEV_FEATURES: {:#018x}  VINTR_CTRL: {:#018x}  EVENTINJ: {:#018x}",
+            { self.sev_features },
+            { self.vintr_ctrl },
+            { self.event_inj }
+        )?;
+        write!(
+            f,
+            "EXITCODE: {:?}  EXITINFO1: {:#018x}  EXITINFO2: {:#018x}  EXITINTINFO: {:#018x}",
+            { self.guest_exit_code },
+            { self.guest_exitinfo1 },
+            { self.guest_exitinfo2 },
+            { self.guest_exitintinfo }
+        )
+    }
+}
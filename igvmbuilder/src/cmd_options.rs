@@ -48,6 +48,13 @@ pub struct CmdOptions {
     #[arg(long)]
     pub policy: Option<String>,
 
+    /// A hex value containing the SVSM service policy, with one nibble of
+    /// allowed VMPLs for each of the core, vTPM, info and debug protocols,
+    /// starting at the lowest nibble. For example: 0x0fff. If not given, the
+    /// SVSM uses its default policy
+    #[arg(long)]
+    pub service_policy: Option<String>,
+
     /// Include NATIVE platform target
     #[arg(long, default_value_t = false)]
     pub native: bool,
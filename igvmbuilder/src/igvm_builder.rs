@@ -207,6 +207,12 @@ impl IgvmBuilder {
             (fw_info, vtom)
         };
 
+        // Zero selects the default policy of the SVSM.
+        let service_policy = match &self.options.service_policy {
+            Some(policy) => u16::from_str_radix(policy.trim_start_matches("0x"), 16)?,
+            None => 0,
+        };
+
         // Most of the parameter block can be initialised with constants.
         let mut param_block = IgvmParamBlock {
             param_area_size,
@@ -216,6 +222,7 @@ impl IgvmBuilder {
             cpuid_page: self.gpa_map.cpuid_page.get_start() as u32,
             secrets_page: self.gpa_map.secrets_page.get_start() as u32,
             debug_serial_port: self.options.get_port_address(),
+            service_policy,
             firmware: fw_info,
             kernel_reserved_size: PAGE_SIZE_4K as u32, // Reserved for VMSA
             kernel_size: self.gpa_map.kernel.get_size() as u32,
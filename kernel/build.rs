@@ -4,6 +4,62 @@
 //
 // Author: Joerg Roedel <jroedel@suse.de>
 
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+fn write_version_file() {
+    let git_hash = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|hash| hash.trim().to_string())
+        .unwrap_or_else(|| String::from("unknown"));
+    let version = format!("{} ({})", env::var("CARGO_PKG_VERSION").unwrap(), git_hash);
+
+    let out_dir = env::var("OUT_DIR").unwrap();
+    fs::write(
+        Path::new(&out_dir).join("version.rs"),
+        format!("pub const SVSM_VERSION_STRING: &str = {:?};\n", version),
+    )
+    .expect("Failed to write version.rs");
+
+    println!("cargo:rerun-if-changed=../.git/HEAD");
+    println!("cargo:rerun-if-changed=../.git/index");
+}
+
+fn write_min_tcb_file() {
+    // Minimum TCB version as boot_loader:tee:snp:microcode
+    let min_tcb = env::var("SVSM_MIN_TCB_VERSION").unwrap_or_else(|_| String::from("0:0:0:0"));
+    let parts = min_tcb
+        .split(':')
+        .map(|part| part.trim().parse::<u8>())
+        .collect::<Result<Vec<_>, _>>()
+        .ok()
+        .filter(|parts| parts.len() == 4)
+        .unwrap_or_else(|| {
+            panic!("SVSM_MIN_TCB_VERSION must be boot_loader:tee:snp:microcode, got {min_tcb:?}")
+        });
+
+    let out_dir = env::var("OUT_DIR").unwrap();
+    fs::write(
+        Path::new(&out_dir).join("min_tcb.rs"),
+        format!(
+            "const MIN_TCB_BOOT_LOADER: u8 = {};\n\
+             const MIN_TCB_TEE: u8 = {};\n\
+             const MIN_TCB_SNP: u8 = {};\n\
+             const MIN_TCB_MICROCODE: u8 = {};\n",
+            parts[0], parts[1], parts[2], parts[3]
+        ),
+    )
+    .expect("Failed to write min_tcb.rs");
+
+    println!("cargo:rerun-if-env-changed=SVSM_MIN_TCB_VERSION");
+}
+
 fn main() {
     // Extra cfgs
     println!("cargo::rustc-check-cfg=cfg(fuzzing)");
@@ -37,6 +93,9 @@ fn main() {
         println!("cargo:rustc-link-arg=-no-pie");
     }
 
+    write_version_file();
+    write_min_tcb_file();
+
     println!("cargo:rerun-if-changed=kernel/src/stage2.lds");
     println!("cargo:rerun-if-changed=kernel/src/svsm.lds");
 }
@@ -45,6 +45,38 @@ pub trait Address:
         Self::from((self.bits() + (align - 1)) & !(align - 1))
     }
 
+    /// Aligns the address up to `align`, which must be a power of two,
+    /// returning `None` on overflow.
+    ///
+    /// ```rust
+    /// # use svsm::address::{Address, PhysAddr};
+    /// assert_eq!(
+    ///     PhysAddr::new(0x1001).checked_align_up(0x1000),
+    ///     Some(PhysAddr::new(0x2000))
+    /// );
+    /// assert_eq!(PhysAddr::new(usize::MAX).checked_align_up(0x1000), None);
+    /// ```
+    #[inline]
+    fn checked_align_up(&self, align: InnerAddr) -> Option<Self> {
+        self.bits()
+            .checked_add(align - 1)
+            .map(|addr| Self::from(addr & !(align - 1)))
+    }
+
+    /// Aligns the address down to `align`, which must be a power of two.
+    ///
+    /// ```rust
+    /// # use svsm::address::{Address, PhysAddr};
+    /// assert_eq!(
+    ///     PhysAddr::new(0x20_1234).align_down(0x20_0000),
+    ///     PhysAddr::new(0x20_0000)
+    /// );
+    /// ```
+    #[inline]
+    fn align_down(&self, align: InnerAddr) -> Self {
+        Self::from(self.bits() & !(align - 1))
+    }
+
     #[inline]
     fn page_align_up(&self) -> Self {
         self.align_up(PAGE_SIZE)
@@ -120,9 +152,16 @@ impl PhysAddr {
     }
 }
 
+/// Addresses are displayed as zero-padded 64-bit hex numbers.
+///
+/// ```rust
+/// # use svsm::address::PhysAddr;
+/// assert_eq!(format!("{}", PhysAddr::new(0xdeadb000)), "0x00000000deadb000");
+/// assert_eq!(format!("{:X}", PhysAddr::new(0xdeadb000)), "DEADB000");
+/// ```
 impl fmt::Display for PhysAddr {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        fmt::Display::fmt(&self.0, f)
+        write!(f, "{:#018x}", self.0)
     }
 }
 
@@ -132,6 +171,12 @@ impl fmt::LowerHex for PhysAddr {
     }
 }
 
+impl fmt::UpperHex for PhysAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::UpperHex::fmt(&self.0, f)
+    }
+}
+
 impl From<InnerAddr> for PhysAddr {
     #[inline]
     fn from(addr: InnerAddr) -> PhysAddr {
@@ -227,6 +272,28 @@ impl VirtAddr {
         self.0 as *mut T
     }
 
+    /// Converts the `VirtAddr` to a pointer to the given type, checking that
+    /// the address is properly aligned for `T`.
+    ///
+    /// # Returns
+    ///
+    /// `None` if the address is not aligned to `mem::align_of::<T>()`.
+    #[inline]
+    pub fn as_aligned_ptr<T>(&self) -> Option<*const T> {
+        self.is_aligned_to::<T>().then(|| self.as_ptr::<T>())
+    }
+
+    /// Converts the `VirtAddr` to a mutable pointer to the given type,
+    /// checking that the address is properly aligned for `T`.
+    ///
+    /// # Returns
+    ///
+    /// `None` if the address is not aligned to `mem::align_of::<T>()`.
+    #[inline]
+    pub fn as_aligned_mut_ptr<T>(&self) -> Option<*mut T> {
+        self.is_aligned_to::<T>().then(|| self.as_mut_ptr::<T>())
+    }
+
     /// Converts the `VirtAddr` to a reference to the given type, checking
     /// that the address is not NULL and properly aligned.
     ///
@@ -235,30 +302,32 @@ impl VirtAddr {
     /// All safety requirements for pointers apply, minus alignment and NULL
     /// checks, which this function already does.
     #[inline]
-    pub unsafe fn aligned_ref<'a, T>(&self) -> Option<&'a T> {
-        self.is_aligned_to::<T>()
-            .then(|| self.as_ptr::<T>().as_ref())
-            .flatten()
+    pub unsafe fn as_aligned_ref<'a, T>(&self) -> Option<&'a T> {
+        self.as_aligned_ptr::<T>().and_then(|ptr| ptr.as_ref())
     }
 
-    /// Converts the `VirtAddr` to a reference to the given type, checking
-    /// that the address is not NULL and properly aligned.
+    /// Converts the `VirtAddr` to a mutable reference to the given type,
+    /// checking that the address is not NULL and properly aligned.
     ///
     /// # Safety
     ///
     /// All safety requirements for pointers apply, minus alignment and NULL
     /// checks, which this function already does.
     #[inline]
-    pub unsafe fn aligned_mut<'a, T>(&self) -> Option<&'a mut T> {
-        self.is_aligned_to::<T>()
-            .then(|| self.as_mut_ptr::<T>().as_mut())
-            .flatten()
+    pub unsafe fn as_aligned_mut<'a, T>(&self) -> Option<&'a mut T> {
+        self.as_aligned_mut_ptr::<T>().and_then(|ptr| ptr.as_mut())
     }
 
     pub const fn const_add(&self, offset: usize) -> Self {
         VirtAddr::new(self.0 + offset)
     }
 
+    /// Returns the address as an integer in constant expressions, where
+    /// [`Address::bits()`] is not available.
+    pub const fn const_bits(&self) -> InnerAddr {
+        self.0
+    }
+
     /// Converts the `VirtAddr` to a slice of a given type
     ///
     /// # Arguments:
@@ -278,9 +347,16 @@ impl VirtAddr {
     }
 }
 
+/// Addresses are displayed as zero-padded 64-bit hex numbers.
+///
+/// ```rust
+/// # use svsm::address::VirtAddr;
+/// assert_eq!(format!("{}", VirtAddr::new(0xdeadb000)), "0x00000000deadb000");
+/// assert_eq!(format!("{:X}", VirtAddr::new(0xdeadb000)), "DEADB000");
+/// ```
 impl fmt::Display for VirtAddr {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        fmt::Display::fmt(&self.0, f)
+        write!(f, "{:#018x}", self.0)
     }
 }
 
@@ -290,6 +366,12 @@ impl fmt::LowerHex for VirtAddr {
     }
 }
 
+impl fmt::UpperHex for VirtAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::UpperHex::fmt(&self.0, f)
+    }
+}
+
 impl From<InnerAddr> for VirtAddr {
     #[inline]
     fn from(addr: InnerAddr) -> Self {
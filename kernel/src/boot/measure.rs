@@ -0,0 +1,107 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2024 SUSE LLC
+
+//! Measurement of the SVSM initialization steps into a boot PCR.
+
+extern crate alloc;
+
+use crate::crypto::digest::{Sha384, Sha384Trait, SHA384_DIGEST_SIZE};
+use crate::locking::SpinLock;
+use alloc::vec::Vec;
+
+/// One entry of the boot event log.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BootEvent {
+    /// Name of the initialization step
+    pub label: &'static str,
+    /// SHA-384 of the label, a NUL byte and the measured data
+    pub digest: [u8; SHA384_DIGEST_SIZE],
+}
+
+#[derive(Debug)]
+struct BootMeasurements {
+    pcr: [u8; SHA384_DIGEST_SIZE],
+    events: Vec<BootEvent>,
+}
+
+static BOOT_MEASUREMENTS: SpinLock<BootMeasurements> = SpinLock::new(BootMeasurements {
+    pcr: [0; SHA384_DIGEST_SIZE],
+    events: Vec::new(),
+});
+
+fn event_digest(label: &str, data: &[u8]) -> [u8; SHA384_DIGEST_SIZE] {
+    // The NUL separator keeps the split between label and data unambiguous.
+    Sha384::digest(&[label.as_bytes(), &[0], data])
+}
+
+fn extend_pcr(
+    pcr: &[u8; SHA384_DIGEST_SIZE],
+    digest: &[u8; SHA384_DIGEST_SIZE],
+) -> [u8; SHA384_DIGEST_SIZE] {
+    Sha384::digest(&[pcr, digest])
+}
+
+/// Measures `data` for the initialization step `label`.
+///
+/// The boot PCR is extended as `SHA384(pcr || SHA384(label || 0 || data))`
+/// and the event is appended to the boot event log.
+pub fn extend_boot_measurement(label: &'static str, data: &[u8]) {
+    let digest = event_digest(label, data);
+    let mut measurements = BOOT_MEASUREMENTS.lock();
+    measurements.pcr = extend_pcr(&measurements.pcr, &digest);
+    measurements.events.push(BootEvent { label, digest });
+}
+
+/// Returns the current value of the boot PCR.
+pub fn get_boot_pcr() -> [u8; SHA384_DIGEST_SIZE] {
+    BOOT_MEASUREMENTS.lock().pcr
+}
+
+/// Returns a copy of the boot event log, in measurement order.
+pub fn boot_event_log() -> Vec<BootEvent> {
+    BOOT_MEASUREMENTS.lock().events.clone()
+}
+
+/// Recomputes the boot PCR from an event log, for checking that a log
+/// matches a reported PCR value.
+pub fn replay_boot_event_log(events: &[BootEvent]) -> [u8; SHA384_DIGEST_SIZE] {
+    events.iter().fold([0; SHA384_DIGEST_SIZE], |pcr, event| {
+        extend_pcr(&pcr, &event.digest)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sha384_parts() {
+        // FIPS 180-2 test vector for "abc"
+        let expected = [
+            0xcb, 0x00, 0x75, 0x3f, 0x45, 0xa3, 0x5e, 0x8b, 0xb5, 0xa0, 0x3d, 0x69, 0x9a, 0xc6,
+            0x50, 0x07, 0x27, 0x2c, 0x32, 0xab, 0x0e, 0xde, 0xd1, 0x63, 0x1a, 0x8b, 0x60, 0x5a,
+            0x43, 0xff, 0x5b, 0xed, 0x80, 0x86, 0x07, 0x2b, 0xa1, 0xe7, 0xcc, 0x23, 0x58, 0xba,
+            0xec, 0xa1, 0x34, 0xc8, 0x25, 0xa7,
+        ];
+        assert_eq!(Sha384::digest(&[b"ab", b"c"]), expected);
+    }
+
+    #[test]
+    fn test_boot_measurement() {
+        // The log is global, so only look at what this test added.
+        let start = boot_event_log().len();
+        extend_boot_measurement("test-step-1", b"one");
+        extend_boot_measurement("test-step-2", b"two");
+
+        let events = boot_event_log();
+        let added = &events[start..];
+        assert_eq!(added.len(), 2);
+        assert_eq!(added[0].label, "test-step-1");
+        assert_eq!(added[0].digest, event_digest("test-step-1", b"one"));
+        assert_eq!(replay_boot_event_log(&events), get_boot_pcr());
+
+        // The label is part of the measurement.
+        assert_ne!(event_digest("a", b"bc"), event_digest("ab", b"c"));
+    }
+}
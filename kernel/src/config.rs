@@ -121,6 +121,13 @@ impl SvsmConfig<'_> {
         }
     }
 
+    pub fn service_policy(&self) -> u16 {
+        match self {
+            SvsmConfig::FirmwareConfig(_) => 0,
+            SvsmConfig::IgvmConfig(igvm_params) => igvm_params.service_policy(),
+        }
+    }
+
     pub fn get_fw_metadata(&self) -> Option<SevFWMetaData> {
         match self {
             SvsmConfig::FirmwareConfig(_) => {
@@ -0,0 +1,90 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2024 SUSE LLC
+
+//! Logger emitting one JSON object per line, for consumption by host-side
+//! tooling. The format is described in `Documentation/docs/developer/JSON-LOG.md`.
+
+use super::_print;
+use crate::cpu::msr::rdtsc;
+use core::fmt;
+
+/// Wraps a [`fmt::Write`] and escapes everything written through it for
+/// use inside a JSON string.
+#[derive(Debug)]
+struct JsonEscape<W: fmt::Write>(W);
+
+impl<W: fmt::Write> fmt::Write for JsonEscape<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for ch in s.chars() {
+            match ch {
+                '"' => self.0.write_str("\\\"")?,
+                '\\' => self.0.write_str("\\\\")?,
+                '\n' => self.0.write_str("\\n")?,
+                '\r' => self.0.write_str("\\r")?,
+                '\t' => self.0.write_str("\\t")?,
+                c if u32::from(c) < 0x20 => write!(self.0, "\\u{:04x}", u32::from(c))?,
+                c => self.0.write_char(c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Adapter to print [`fmt::Arguments`] as an escaped JSON string.
+#[derive(Debug)]
+struct JsonStr<'a>(fmt::Arguments<'a>);
+
+impl fmt::Display for JsonStr<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::write(&mut JsonEscape(f), self.0)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct JsonLogSink {
+    component: &'static str,
+}
+
+impl JsonLogSink {
+    pub const fn new(component: &'static str) -> Self {
+        Self { component }
+    }
+}
+
+impl log::Log for JsonLogSink {
+    fn enabled(&self, metadata: &log::Metadata<'_>) -> bool {
+        super::log_filter::log_enabled(metadata)
+    }
+
+    fn log(&self, record: &log::Record<'_>) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        _print(format_args!(
+            "{{\"level\":\"{}\",\"ts_tsc\":{},\"component\":\"{}\",\"target\":\"{}\",\"msg\":\"{}\"}}\n",
+            record.level().as_str(),
+            rdtsc(),
+            JsonStr(format_args!("{}", self.component)),
+            JsonStr(format_args!("{}", record.target())),
+            JsonStr(*record.args()),
+        ));
+    }
+
+    fn flush(&self) {}
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+
+    use super::*;
+    use alloc::format;
+
+    #[test]
+    fn test_json_escape() {
+        let s = format!("{}", JsonStr(format_args!("a\"b\\c\nd\u{1}")));
+        assert_eq!(s, "a\\\"b\\\\c\\nd\\u0001");
+    }
+}
@@ -0,0 +1,142 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2024 SUSE LLC
+
+//! Runtime filtering of log messages by module.
+//!
+//! Note that messages above the level selected through the `log` crate
+//! features in `Cargo.toml` are compiled out and can't be enabled at
+//! runtime.
+
+extern crate alloc;
+
+use crate::locking::RWLock;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::str::FromStr;
+use core::sync::atomic::{AtomicBool, Ordering};
+use log::LevelFilter;
+
+#[derive(Debug)]
+pub struct LogFilter {
+    /// Level for modules not matching any rule
+    default: LevelFilter,
+    /// Per-module levels, sorted by module prefix
+    rules: Vec<(String, LevelFilter)>,
+}
+
+impl LogFilter {
+    pub const fn new(default: LevelFilter) -> Self {
+        Self {
+            default,
+            rules: Vec::new(),
+        }
+    }
+
+    pub fn set_default(&mut self, level: LevelFilter) {
+        self.default = level;
+    }
+
+    /// Sets the level for `module` and all modules below it.
+    pub fn set(&mut self, module: &str, level: LevelFilter) {
+        match self
+            .rules
+            .binary_search_by(|(prefix, _)| prefix.as_str().cmp(module))
+        {
+            Ok(idx) => self.rules[idx].1 = level,
+            Err(idx) => self.rules.insert(idx, (String::from(module), level)),
+        }
+    }
+
+    /// Returns the level for `target`, as set by the rule with the longest
+    /// matching module prefix.
+    pub fn level(&self, target: &str) -> LevelFilter {
+        self.rules
+            .iter()
+            .filter(|(prefix, _)| module_matches(prefix, target))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map_or(self.default, |(_, level)| *level)
+    }
+
+    pub fn enabled(&self, metadata: &log::Metadata<'_>) -> bool {
+        metadata.level() <= self.level(metadata.target())
+    }
+
+    /// Parses a filter specification of the form
+    /// `<level>,<module>=<level>,...` into the filter. Malformed entries
+    /// are skipped.
+    pub fn parse(&mut self, spec: &str) {
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            match entry.split_once('=') {
+                Some((module, level)) => {
+                    if let Ok(level) = LevelFilter::from_str(level.trim()) {
+                        self.set(module.trim(), level);
+                    }
+                }
+                None => {
+                    if let Ok(level) = LevelFilter::from_str(entry) {
+                        self.default = level;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Whether `target` is `module` or one of its submodules.
+fn module_matches(module: &str, target: &str) -> bool {
+    target
+        .strip_prefix(module)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+}
+
+static LOG_FILTER: RWLock<LogFilter> = RWLock::new(LogFilter::new(LevelFilter::Trace));
+static LOG_FORCE_TRACE: AtomicBool = AtomicBool::new(false);
+
+/// Sets the log level for `module` and its submodules.
+pub fn set_log_level(module: &str, level: LevelFilter) {
+    LOG_FILTER.lock_write().set(module, level);
+}
+
+/// Sets the log level for modules without a specific level.
+pub fn set_default_log_level(level: LevelFilter) {
+    LOG_FILTER.lock_write().set_default(level);
+}
+
+/// Applies a filter specification like `debug,svsm::mm=trace`, see
+/// [`LogFilter::parse()`].
+pub fn parse_log_filter(spec: &str) {
+    LOG_FILTER.lock_write().parse(spec);
+}
+
+/// Enables all log messages regardless of the configured levels. Meant
+/// for the panic path, so it does not take any locks.
+pub fn log_force_trace() {
+    LOG_FORCE_TRACE.store(true, Ordering::Relaxed);
+}
+
+pub fn log_enabled(metadata: &log::Metadata<'_>) -> bool {
+    LOG_FORCE_TRACE.load(Ordering::Relaxed) || LOG_FILTER.lock_read().enabled(metadata)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_filter_longest_prefix() {
+        let mut filter = LogFilter::new(LevelFilter::Info);
+        filter.parse("warn, svsm::mm=trace,svsm::mm::alloc=error,svsm::greq=bogus");
+
+        assert_eq!(filter.level("svsm::cpu"), LevelFilter::Warn);
+        assert_eq!(filter.level("svsm::mm"), LevelFilter::Trace);
+        assert_eq!(filter.level("svsm::mm::pagetable"), LevelFilter::Trace);
+        assert_eq!(filter.level("svsm::mm::alloc"), LevelFilter::Error);
+        assert_eq!(filter.level("svsm::mmio"), LevelFilter::Warn);
+        assert_eq!(filter.level("svsm::greq"), LevelFilter::Warn);
+
+        filter.set("svsm::mm", LevelFilter::Off);
+        assert_eq!(filter.level("svsm::mm::vm"), LevelFilter::Off);
+        assert_eq!(filter.rules.len(), 2);
+    }
+}
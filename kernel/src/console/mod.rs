@@ -4,21 +4,40 @@
 //
 // Author: Joerg Roedel <jroedel@suse.de>
 
+#[cfg(feature = "json-log")]
+pub mod jsonlog;
+pub mod log_filter;
+pub mod ring;
+
 use crate::locking::SpinLock;
 use crate::serial::{Terminal, DEFAULT_SERIAL_PORT};
 use crate::utils::immut_after_init::{ImmutAfterInitCell, ImmutAfterInitResult};
 use core::fmt;
+use ring::{console_buf_drain, console_buf_is_empty, console_buf_write};
 
 #[derive(Clone, Copy)]
 struct Console {
     writer: &'static dyn Terminal,
 }
 
-impl fmt::Write for Console {
+impl Console {
+    fn flush(&self) {
+        console_buf_drain(|data| {
+            for ch in data {
+                self.writer.put_byte(*ch);
+            }
+        });
+    }
+}
+
+/// Writes into the console ring buffer. The data reaches the serial port
+/// when the ring buffer is drained.
+#[derive(Clone, Copy, Debug)]
+struct ConsoleBufWriter;
+
+impl fmt::Write for ConsoleBufWriter {
     fn write_str(&mut self, s: &str) -> fmt::Result {
-        for ch in s.bytes() {
-            self.writer.put_byte(ch);
-        }
+        console_buf_write(s.as_bytes());
         Ok(())
     }
 }
@@ -47,19 +66,43 @@ pub fn _print(args: fmt::Arguments<'_>) {
     if !*CONSOLE_INITIALIZED {
         return;
     }
-    WRITER.lock().write_fmt(args).unwrap();
+    ConsoleBufWriter.write_fmt(args).unwrap();
+
+    // Drain the ring buffer unless another context is already doing so, in
+    // which case it will also pick up the data written above. Check again
+    // after dropping the lock, as data written by another context between
+    // the last drain and the unlock would otherwise stay in the buffer.
+    while let Some(writer) = WRITER.try_lock() {
+        writer.flush();
+        drop(writer);
+        if console_buf_is_empty() {
+            break;
+        }
+    }
+}
+
+/// Synchronously writes all buffered console output to the serial port.
+/// Meant for the panic path, where output must not be left in the buffer.
+pub fn console_flush() {
+    if !*CONSOLE_INITIALIZED {
+        return;
+    }
+    WRITER.lock().flush();
 }
 
+#[cfg_attr(feature = "json-log", allow(dead_code))]
 #[derive(Clone, Copy, Debug)]
 struct ConsoleLoggerComponent {
     name: &'static str,
 }
 
+#[cfg_attr(feature = "json-log", allow(dead_code))]
 #[derive(Clone, Copy, Debug)]
 struct ConsoleLogger {
     component: ConsoleLoggerComponent,
 }
 
+#[cfg_attr(feature = "json-log", allow(dead_code))]
 impl ConsoleLogger {
     fn new(component: &'static str) -> ConsoleLogger {
         ConsoleLogger {
@@ -69,8 +112,8 @@ impl ConsoleLogger {
 }
 
 impl log::Log for ConsoleLogger {
-    fn enabled(&self, _metadata: &log::Metadata<'_>) -> bool {
-        true
+    fn enabled(&self, metadata: &log::Metadata<'_>) -> bool {
+        log_filter::log_enabled(metadata)
     }
 
     fn log(&self, record: &log::Record<'_>) {
@@ -111,10 +154,15 @@ impl log::Log for ConsoleLogger {
     fn flush(&self) {}
 }
 
-static CONSOLE_LOGGER: ImmutAfterInitCell<ConsoleLogger> = ImmutAfterInitCell::uninit();
+#[cfg(not(feature = "json-log"))]
+type Logger = ConsoleLogger;
+#[cfg(feature = "json-log")]
+type Logger = jsonlog::JsonLogSink;
+
+static CONSOLE_LOGGER: ImmutAfterInitCell<Logger> = ImmutAfterInitCell::uninit();
 
 pub fn install_console_logger(component: &'static str) -> ImmutAfterInitResult<()> {
-    CONSOLE_LOGGER.init(&ConsoleLogger::new(component))?;
+    CONSOLE_LOGGER.init(&Logger::new(component))?;
 
     if let Err(e) = log::set_logger(&*CONSOLE_LOGGER) {
         // Failed to install the ConsoleLogger, presumably because something had
@@ -0,0 +1,137 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2024 SUSE LLC
+
+//! Ring buffer decoupling console writers from the speed of the serial
+//! port.
+
+use crate::locking::SpinLock;
+use core::sync::atomic::{AtomicU64, Ordering};
+
+/// Size of the console ring buffer in bytes.
+pub const CONSOLE_BUF_SIZE: usize = 16384;
+
+#[derive(Debug)]
+pub struct ConsoleBuf<const N: usize> {
+    buf: [u8; N],
+    /// Index of the oldest byte in the buffer
+    head: usize,
+    /// Number of bytes in the buffer
+    len: usize,
+}
+
+impl<const N: usize> ConsoleBuf<N> {
+    pub const fn new() -> Self {
+        Self {
+            buf: [0; N],
+            head: 0,
+            len: 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Appends as many bytes of `data` as fit into the buffer.
+    ///
+    /// # Returns
+    ///
+    /// `true` if all of `data` was stored, `false` if it was truncated.
+    pub fn push(&mut self, data: &[u8]) -> bool {
+        let count = data.len().min(N - self.len);
+        for (i, byte) in data[..count].iter().enumerate() {
+            self.buf[(self.head + self.len + i) % N] = *byte;
+        }
+        self.len += count;
+        count == data.len()
+    }
+
+    /// Removes up to `out.len()` of the oldest bytes from the buffer and
+    /// copies them into `out`.
+    ///
+    /// # Returns
+    ///
+    /// The number of bytes copied.
+    pub fn pop(&mut self, out: &mut [u8]) -> usize {
+        let count = out.len().min(self.len);
+        for (i, byte) in out[..count].iter_mut().enumerate() {
+            *byte = self.buf[(self.head + i) % N];
+        }
+        self.head = (self.head + count) % N;
+        self.len -= count;
+        count
+    }
+}
+
+impl<const N: usize> Default for ConsoleBuf<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+static CONSOLE_BUF: SpinLock<ConsoleBuf<CONSOLE_BUF_SIZE>> = SpinLock::new(ConsoleBuf::new());
+static CONSOLE_BUF_OVERFLOWS: AtomicU64 = AtomicU64::new(0);
+
+/// Appends `data` to the console ring buffer without waiting for the
+/// serial port. Data which does not fit is dropped.
+pub fn console_buf_write(data: &[u8]) {
+    if !CONSOLE_BUF.lock().push(data) {
+        CONSOLE_BUF_OVERFLOWS.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Moves data from the console ring buffer to `out` in chunks until the
+/// buffer is empty. The ring buffer lock is not held while `out` runs.
+pub fn console_buf_drain(mut out: impl FnMut(&[u8])) {
+    let mut chunk = [0u8; 256];
+    loop {
+        let count = CONSOLE_BUF.lock().pop(&mut chunk);
+        if count == 0 {
+            break;
+        }
+        out(&chunk[..count]);
+    }
+}
+
+pub fn console_buf_is_empty() -> bool {
+    CONSOLE_BUF.lock().is_empty()
+}
+
+/// Returns the number of console writes which were dropped or truncated
+/// because the ring buffer was full.
+pub fn console_buf_overflow_count() -> u64 {
+    CONSOLE_BUF_OVERFLOWS.load(Ordering::Relaxed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_console_buf_wrap() {
+        let mut buf = ConsoleBuf::<8>::new();
+        let mut out = [0u8; 8];
+
+        assert!(buf.push(b"abcdef"));
+        assert_eq!(buf.pop(&mut out[..4]), 4);
+        assert_eq!(&out[..4], b"abcd");
+
+        // Wraps around the end of the buffer.
+        assert!(buf.push(b"ghijkl"));
+        assert_eq!(buf.pop(&mut out), 8);
+        assert_eq!(&out, b"efghijkl");
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn test_console_buf_overflow() {
+        let mut buf = ConsoleBuf::<4>::new();
+        let mut out = [0u8; 8];
+
+        assert!(!buf.push(b"abcdef"));
+        assert_eq!(buf.pop(&mut out), 4);
+        assert_eq!(&out[..4], b"abcd");
+        assert_eq!(buf.pop(&mut out), 0);
+    }
+}
@@ -0,0 +1,102 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2024 SUSE LLC
+
+//! Discovery of the hypervisor features advertised through the GHCB
+//! protocol.
+
+use crate::cpu::msr::{read_msr, write_msr, SEV_GHCB};
+use crate::sev::msr_protocol::{GHCBHvFeatures, GHCBMsr, GhcbMsrError};
+use crate::sev::utils::raw_vmgexit;
+use crate::utils::immut_after_init::ImmutAfterInitCell;
+
+/// Hypervisor features as returned by the `SNP_HV_FEATURES` GHCB MSR
+/// protocol request.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HvFeatures(u64);
+
+impl HvFeatures {
+    pub const fn new(bits: u64) -> Self {
+        Self(bits)
+    }
+
+    pub const fn bits(&self) -> u64 {
+        self.0
+    }
+
+    pub fn flags(&self) -> GHCBHvFeatures {
+        GHCBHvFeatures::from_bits_truncate(self.0)
+    }
+
+    /// Whether the hypervisor supports SEV-SNP guests at all.
+    pub fn has_snp(&self) -> bool {
+        self.flags().contains(GHCBHvFeatures::SEV_SNP)
+    }
+
+    /// Whether the hypervisor implements the `SNP_AP_CREATION` NAE event.
+    pub fn has_ap_create(&self) -> bool {
+        self.flags().contains(GHCBHvFeatures::SEV_SNP_AP_CREATION)
+    }
+
+    /// Whether the hypervisor implements the APIC ID list request, which
+    /// extends AP creation with the enumeration of all vCPUs.
+    pub fn has_snp_ap_extension(&self) -> bool {
+        self.flags().contains(GHCBHvFeatures::APIC_ID_LIST)
+    }
+
+    /// Whether the hypervisor supports restricted injection.
+    pub fn has_restricted_injection(&self) -> bool {
+        self.flags().contains(GHCBHvFeatures::SEV_SNP_RESTR_INJ)
+    }
+
+    /// Whether the hypervisor supports running guests at multiple VMPLs.
+    pub fn has_multi_vmpl(&self) -> bool {
+        self.flags().contains(GHCBHvFeatures::SEV_SNP_MULTI_VMPL)
+    }
+}
+
+static HV_FEATURES: ImmutAfterInitCell<HvFeatures> = ImmutAfterInitCell::uninit();
+
+/// Query the hypervisor for its supported features using the GHCB MSR
+/// protocol.
+pub fn query_hv_features() -> Result<HvFeatures, GhcbMsrError> {
+    write_msr(SEV_GHCB, GHCBMsr::SNP_HV_FEATURES_REQ);
+    raw_vmgexit();
+    let result = read_msr(SEV_GHCB);
+    if (result & 0xFFF) != GHCBMsr::SNP_HV_FEATURES_RESP {
+        return Err(GhcbMsrError::InfoMismatch);
+    }
+
+    Ok(HvFeatures::new(result >> 12))
+}
+
+/// Query the hypervisor features and store them for later use via
+/// [`hv_features()`].
+pub fn init_hv_features() -> Result<(), GhcbMsrError> {
+    let features = query_hv_features()?;
+
+    // Verify that the required features are supported.
+    let required = GHCBHvFeatures::SEV_SNP
+        | GHCBHvFeatures::SEV_SNP_AP_CREATION
+        | GHCBHvFeatures::SEV_SNP_MULTI_VMPL;
+    let missing = !features.flags() & required;
+    if !missing.is_empty() {
+        log::error!(
+            "Required hypervisor GHCB features not available: present={:#x}, required={:#x}, missing={:#x}",
+            features.flags(), required, missing
+        );
+        // FIXME - enforce this panic once KVM advertises the required
+        // features.
+        // panic!("Required hypervisor GHCB features not available");
+    }
+
+    HV_FEATURES
+        .init(&features)
+        .expect("Already initialized GHCB HV features");
+    Ok(())
+}
+
+/// Returns the hypervisor features discovered by [`init_hv_features()`].
+pub fn hv_features() -> HvFeatures {
+    *HV_FEATURES
+}
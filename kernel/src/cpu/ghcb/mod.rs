@@ -0,0 +1,176 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) Microsoft Corporation
+//
+// Author: Jon Lange (jlange@microsoft.com)
+
+pub mod hv_features;
+
+use crate::cpu::percpu::this_cpu_unsafe;
+use crate::error::SvsmError;
+use crate::mm::alloc::{allocate_zeroed_page, free_page};
+use crate::sev::ghcb::GHCB;
+use crate::sev::vmsa::VMPL_MAX;
+
+use core::cell::Cell;
+use core::ops::{Deref, DerefMut};
+use core::ptr;
+
+/// Per-CPU set of GHCB pages, holding one page for each VMPL. Requests made
+/// on behalf of different VMPLs use separate pages so that their GHCB state
+/// does not get mixed up. All pages are allocated during CPU setup, so that
+/// acquiring one never allocates memory.
+#[derive(Debug)]
+pub struct GhcbPool {
+    ghcbs: [Cell<*mut GHCB>; VMPL_MAX],
+    /// Index of the GHCB page currently registered with the hypervisor.
+    registered: Cell<Option<usize>>,
+}
+
+impl GhcbPool {
+    pub const fn new() -> Self {
+        Self {
+            ghcbs: [const { Cell::new(ptr::null_mut()) }; VMPL_MAX],
+            registered: Cell::new(None),
+        }
+    }
+
+    fn alloc_index(&self, idx: usize) -> Result<*mut GHCB, SvsmError> {
+        let ghcb = self.ghcbs[idx].get();
+        if !ghcb.is_null() {
+            return Ok(ghcb);
+        }
+
+        let ghcb_page = allocate_zeroed_page()?;
+        if let Err(e) = GHCB::init(ghcb_page) {
+            free_page(ghcb_page);
+            return Err(e);
+        }
+        self.ghcbs[idx].set(ghcb_page.as_mut_ptr());
+        Ok(ghcb_page.as_mut_ptr())
+    }
+
+    /// Allocates and initializes the GHCB pages of all VMPLs.
+    pub fn init(&mut self) -> Result<(), SvsmError> {
+        for idx in 0..VMPL_MAX {
+            self.alloc_index(idx)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the raw pointer to the GHCB page used for `vmpl`, which is
+    /// NULL if the page has not been allocated yet.
+    pub fn ghcb_unsafe(&self, vmpl: u8) -> *mut GHCB {
+        self.ghcbs[usize::from(vmpl)].get()
+    }
+
+    fn register_index(&self, idx: usize) -> Result<(), SvsmError> {
+        // SAFETY: non-NULL entries always point to GHCB pages set up in
+        // `alloc_index()`.
+        let ghcb = unsafe { self.ghcbs[idx].get().as_ref() }.ok_or(SvsmError::Mem)?;
+        ghcb.register()?;
+        self.registered.set(Some(idx));
+        Ok(())
+    }
+
+    /// Registers the GHCB page of `vmpl` with the hypervisor.
+    pub fn register(&self, vmpl: u8) -> Result<(), SvsmError> {
+        self.register_index(usize::from(vmpl))
+    }
+
+    /// Hands out the GHCB page for `vmpl`, registering it with the
+    /// hypervisor first if a different page of the pool is currently
+    /// registered.
+    ///
+    /// Fails with [`SvsmError::InvalidParameter`] if `vmpl` is not a valid
+    /// VMPL and with [`SvsmError::Mem`] if the pool has not been set up yet.
+    pub fn acquire_for_vmpl(&self, vmpl: u8) -> Result<GhcbGuard, SvsmError> {
+        let idx = usize::from(vmpl);
+        let ghcb = self
+            .ghcbs
+            .get(idx)
+            .ok_or(SvsmError::InvalidParameter)?
+            .get();
+        if ghcb.is_null() {
+            return Err(SvsmError::Mem);
+        }
+
+        // The hypervisor only accepts the GHCB page that was last
+        // registered, so switch the registration if needed.
+        if self.registered.get().is_some_and(|reg| reg != idx) {
+            self.register_index(idx)?;
+        }
+
+        Ok(GhcbGuard { ghcb })
+    }
+
+    /// Shuts down all GHCB pages of the pool.
+    pub fn shutdown(&mut self) -> Result<(), SvsmError> {
+        for ghcb in self
+            .ghcbs
+            .iter()
+            .map(Cell::get)
+            .filter(|ghcb| !ghcb.is_null())
+        {
+            // SAFETY: non-NULL entries always point to GHCB pages set up in
+            // `alloc_index()`.
+            unsafe { (*ghcb).shutdown()? };
+        }
+        self.registered.set(None);
+
+        Ok(())
+    }
+}
+
+impl Default for GhcbPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// RAII guard granting access to a GHCB page from a [`GhcbPool`]. The valid
+/// bitmap of the GHCB is reset when the guard is dropped, so that no stale
+/// state leaks into the next request.
+#[derive(Debug)]
+pub struct GhcbGuard {
+    ghcb: *mut GHCB,
+}
+
+impl Deref for GhcbGuard {
+    type Target = GHCB;
+    fn deref(&self) -> &'static GHCB {
+        unsafe { &*self.ghcb }
+    }
+}
+
+impl DerefMut for GhcbGuard {
+    fn deref_mut(&mut self) -> &'static mut GHCB {
+        unsafe { &mut *self.ghcb }
+    }
+}
+
+impl Drop for GhcbGuard {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+/// Acquires the GHCB of the current CPU on behalf of `vmpl`.
+pub fn this_cpu_ghcb(vmpl: u8) -> Result<GhcbGuard, SvsmError> {
+    // FIXME - Add borrow checking to GHCB references.
+    unsafe {
+        let cpu_unsafe = &*this_cpu_unsafe();
+        cpu_unsafe.ghcb_pool().acquire_for_vmpl(vmpl)
+    }
+}
+
+/// Acquires the GHCB the SVSM uses for its own requests on the current CPU.
+///
+/// # Panics
+///
+/// Panics if the GHCB pages of this CPU are not set up yet, or if the page
+/// of VMPL0 has to be registered again and the hypervisor rejects it. The
+/// SVSM cannot continue without a GHCB in either case.
+pub fn current_ghcb() -> GhcbGuard {
+    this_cpu_ghcb(0).expect("Failed to acquire the GHCB of VMPL0")
+}
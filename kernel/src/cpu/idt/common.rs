@@ -9,8 +9,10 @@ use crate::cpu::registers::{X86GeneralRegs, X86InterruptFrame};
 use crate::locking::{RWLock, ReadLockGuard, WriteLockGuard};
 use crate::types::SVSM_CS;
 use core::arch::{asm, global_asm};
+use core::fmt;
 use core::mem;
 use core::ptr::addr_of;
+use core::slice;
 
 pub const DE_VECTOR: usize = 0;
 pub const DB_VECTOR: usize = 1;
@@ -46,10 +48,90 @@ pub struct X86ExceptionContext {
     pub frame: X86InterruptFrame,
 }
 
+// The layout must match what the exception entry code pushes on the stack.
+const _: () = assert!(mem::size_of::<X86ExceptionContext>() == 21 * 8);
+
+impl fmt::Display for X86ExceptionContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Fields are copied out with braces as the struct is packed.
+        writeln!(
+            f,
+            "RIP: {:#018x}  CS:  {:#06x}  RFLAGS: {:#018x}",
+            { self.frame.rip },
+            { self.frame.cs },
+            { self.frame.flags }
+        )?;
+        writeln!(
+            f,
+            "RSP: {:#018x}  SS:  {:#06x}  ERROR:  {:#018x}",
+            { self.frame.rsp },
+            { self.frame.ss },
+            { self.error_code }
+        )?;
+        let regs = self.regs;
+        write!(f, "{}", regs)
+    }
+}
+
 pub fn user_mode(ctxt: &X86ExceptionContext) -> bool {
     (ctxt.frame.cs & 3) == 3
 }
 
+/// Descriptor table referenced by a selector error code.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DescriptorTable {
+    Gdt,
+    Idt,
+    Ldt,
+}
+
+/// Decoded form of the selector error code pushed by #GP and the other
+/// segment-related exceptions.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct GpErrorCode {
+    pub external: bool,
+    pub table: DescriptorTable,
+    pub selector_index: u16,
+}
+
+pub fn decode_gp_error(code: u32) -> GpErrorCode {
+    let table = if code & 0x2 != 0 {
+        DescriptorTable::Idt
+    } else if code & 0x4 != 0 {
+        DescriptorTable::Ldt
+    } else {
+        DescriptorTable::Gdt
+    };
+
+    GpErrorCode {
+        external: code & 0x1 != 0,
+        table,
+        selector_index: ((code >> 3) & 0x1fff) as u16,
+    }
+}
+
+/// Returns the current DS, ES, FS and GS selectors, in that order.
+pub fn data_segment_selectors() -> [u16; 4] {
+    let ds: u16;
+    let es: u16;
+    let fs: u16;
+    let gs: u16;
+
+    unsafe {
+        asm!("movw %ds, {0:x}
+              movw %es, {1:x}
+              movw %fs, {2:x}
+              movw %gs, {3:x}",
+             out(reg) ds,
+             out(reg) es,
+             out(reg) fs,
+             out(reg) gs,
+             options(att_syntax, nomem, nostack, preserves_flags));
+    }
+
+    [ds, es, fs, gs]
+}
+
 #[derive(Copy, Clone, Default, Debug)]
 #[repr(C, packed)]
 pub struct IdtEntry {
@@ -226,17 +308,56 @@ pub fn triple_fault() {
 }
 
 extern "C" {
-    static generic_idt_handler_return: u8;
+    static exception_return_sites: i32;
+    static exception_return_sites_end: i32;
+    static stage2_exception_return_sites: i32;
+    static stage2_exception_return_sites_end: i32;
+}
+
+/// # Safety
+///
+/// `start` and `end` must delimit an array of `i32` in static memory.
+unsafe fn return_site_table(start: *const i32, end: *const i32) -> &'static [i32] {
+    let len = (end as usize - start as usize) / mem::size_of::<i32>();
+    slice::from_raw_parts(start, len)
+}
+
+/// Returns the sorted tables of return addresses of the calls made by the
+/// assembly exception entry points. Each entry is the offset of the return
+/// address from the entry itself.
+fn exception_return_site_tables() -> [&'static [i32]; 2] {
+    // SAFETY: the symbols delimit the tables emitted by the exception entry
+    // code.
+    unsafe {
+        [
+            return_site_table(
+                addr_of!(exception_return_sites),
+                addr_of!(exception_return_sites_end),
+            ),
+            return_site_table(
+                addr_of!(stage2_exception_return_sites),
+                addr_of!(stage2_exception_return_sites_end),
+            ),
+        ]
+    }
+}
+
+/// Decodes the return address stored in a return site table entry.
+fn return_site_address(entry: &i32) -> u64 {
+    (entry as *const i32 as u64).wrapping_add_signed(i64::from(*entry))
 }
 
 pub fn is_exception_handler_return_site(rip: VirtAddr) -> bool {
-    let addr = unsafe { VirtAddr::from(addr_of!(generic_idt_handler_return)) };
-    addr == rip
+    let rip = rip.bits() as u64;
+    exception_return_site_tables().iter().any(|table| {
+        table
+            .binary_search_by_key(&rip, return_site_address)
+            .is_ok()
+    })
 }
 
 global_asm!(
     r#"
-        /* Needed by the stack unwinder to recognize exception frames. */
         .globl generic_idt_handler_return
     generic_idt_handler_return:
 
@@ -262,3 +383,75 @@ global_asm!(
         "#,
     options(att_syntax)
 );
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    extern crate alloc;
+    use alloc::format;
+
+    #[test]
+    fn test_exception_context_display() {
+        let mut ctx = X86ExceptionContext::default();
+        ctx.frame.rip = 0xffff_ff80_0000_1234;
+        ctx.regs.r8 = 0x42;
+        ctx.error_code = 0x10;
+
+        let out = format!("{}", ctx);
+        assert!(out.contains("RIP: 0xffffff8000001234"));
+        assert!(out.contains("ERROR:  0x0000000000000010"));
+        assert!(out.contains("R8:  0x0000000000000042"));
+        assert_eq!(out.lines().count(), 6);
+    }
+
+    #[test]
+    fn test_exception_return_sites() {
+        extern "C" {
+            static process_hv_events_return: u8;
+        }
+
+        for table in exception_return_site_tables() {
+            assert!(!table.is_empty());
+            assert!(table
+                .windows(2)
+                .all(|w| return_site_address(&w[0]) < return_site_address(&w[1])));
+            assert!(is_exception_handler_return_site(VirtAddr::from(
+                return_site_address(&table[0])
+            )));
+        }
+        // SAFETY: only the address of the symbol is taken.
+        let hv_return = unsafe { addr_of!(process_hv_events_return) };
+        assert!(is_exception_handler_return_site(VirtAddr::from(hv_return)));
+        assert!(!is_exception_handler_return_site(VirtAddr::from(
+            0xdead_beefu64
+        )));
+    }
+
+    #[test]
+    fn test_decode_gp_error() {
+        assert_eq!(
+            decode_gp_error(0),
+            GpErrorCode {
+                external: false,
+                table: DescriptorTable::Gdt,
+                selector_index: 0,
+            }
+        );
+        assert_eq!(
+            decode_gp_error((13 << 3) | 0x3),
+            GpErrorCode {
+                external: true,
+                table: DescriptorTable::Idt,
+                selector_index: 13,
+            }
+        );
+        assert_eq!(
+            decode_gp_error((5 << 3) | 0x4),
+            GpErrorCode {
+                external: false,
+                table: DescriptorTable::Ldt,
+                selector_index: 5,
+            }
+        );
+    }
+}
@@ -7,5 +7,6 @@
 pub mod common;
 pub mod stage2;
 pub mod svsm;
+pub mod ud;
 
 pub use common::{idt, idt_mut};
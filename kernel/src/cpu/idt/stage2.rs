@@ -4,7 +4,10 @@
 //
 // Author: Joerg Roedel <jroedel@suse.de>
 
-use super::common::{idt_mut, DF_VECTOR, HV_VECTOR, VC_VECTOR};
+use super::common::{
+    data_segment_selectors, decode_gp_error, idt_mut, DF_VECTOR, GP_VECTOR, HV_VECTOR, PF_VECTOR,
+    VC_VECTOR,
+};
 use crate::cpu::control_regs::read_cr2;
 use crate::cpu::vc::{stage2_handle_vc_exception, stage2_handle_vc_exception_no_ghcb};
 use crate::cpu::X86ExceptionContext;
@@ -27,32 +30,61 @@ pub fn early_idt_init() {
     }
 }
 
+/// Reports a page fault taken during stage2 and aborts. There is no demand
+/// paging in stage2, so any #PF is a bug.
+pub fn stage2_page_fault_handler(ctx: &X86ExceptionContext) -> ! {
+    let cr2 = read_cr2();
+
+    panic!("Page fault at VA {:#018x}\n{}", cr2, ctx);
+}
+
+/// Reports a general protection fault taken during stage2 and aborts. The
+/// error code and segment selectors are decoded to make segment register
+/// corruption easier to diagnose.
+pub fn stage2_gp_handler(ctx: &X86ExceptionContext) -> ! {
+    let err = ctx.error_code;
+    let cs = ctx.frame.cs;
+    let ss = ctx.frame.ss;
+    let gp = decode_gp_error(err as u32);
+    let [ds, es, fs, gs] = data_segment_selectors();
+
+    log::error!(
+        "#GP error code {:#x}: external={} table={:?} index={}",
+        err,
+        gp.external,
+        gp.table,
+        gp.selector_index
+    );
+    log::error!(
+        "CS={:#06x} (RPL {}) SS={:#06x} (RPL {}) DS={:#06x} ES={:#06x} FS={:#06x} GS={:#06x}",
+        cs,
+        cs & 3,
+        ss,
+        ss & 3,
+        ds,
+        es,
+        fs,
+        gs
+    );
+
+    panic!("General protection fault\n{}", ctx);
+}
+
 #[no_mangle]
 pub extern "C" fn stage2_generic_idt_handler(ctx: &mut X86ExceptionContext, vector: usize) {
     match vector {
         DF_VECTOR => {
             let cr2 = read_cr2();
-            let rip = ctx.frame.rip;
-            let rsp = ctx.frame.rsp;
-            panic!(
-                "Double-Fault at RIP {:#018x} RSP: {:#018x} CR2: {:#018x}",
-                rip, rsp, cr2
-            );
+            panic!("Double-Fault CR2: {:#018x}\n{}", cr2, ctx);
         }
+        GP_VECTOR => stage2_gp_handler(ctx),
+        PF_VECTOR => stage2_page_fault_handler(ctx),
         VC_VECTOR => stage2_handle_vc_exception(ctx).expect("Failed to handle #VC"),
         HV_VECTOR =>
             // #HV does not require processing during stage 2 and can be
         // completely ignored.
             {}
-        _ => {
-            let err = ctx.error_code;
-            let rip = ctx.frame.rip;
-
-            panic!(
-                "Unhandled exception {} RIP {:#018x} error code: {:#018x}",
-                vector, rip, err
-            );
-        }
+        _ => panic!("Unhandled exception {}\n{}", vector, ctx),
     }
 }
 
@@ -61,23 +93,12 @@ pub extern "C" fn stage2_generic_idt_handler_no_ghcb(ctx: &mut X86ExceptionConte
     match vector {
         DF_VECTOR => {
             let cr2 = read_cr2();
-            let rip = ctx.frame.rip;
-            let rsp = ctx.frame.rsp;
-            panic!(
-                "Double-Fault at RIP {:#018x} RSP: {:#018x} CR2: {:#018x}",
-                rip, rsp, cr2
-            );
+            panic!("Double-Fault CR2: {:#018x}\n{}", cr2, ctx);
         }
+        GP_VECTOR => stage2_gp_handler(ctx),
+        PF_VECTOR => stage2_page_fault_handler(ctx),
         VC_VECTOR => stage2_handle_vc_exception_no_ghcb(ctx).expect("Failed to handle #VC"),
-        _ => {
-            let err = ctx.error_code;
-            let rip = ctx.frame.rip;
-
-            panic!(
-                "Unhandled exception {} RIP {:#018x} error code: {:#018x}",
-                vector, rip, err
-            );
-        }
+        _ => panic!("Unhandled exception {}\n{}", vector, ctx),
     }
 }
 
@@ -88,6 +109,17 @@ extern "C" {
 
 global_asm!(
     r#"
+        /*
+         * Return addresses of the calls into the exception handlers, used by
+         * the stack unwinder to recognize exception frames. Entries are
+         * offsets relative to the entry itself, see entry.S.
+         */
+        .pushsection .rodata
+        .balign 4
+        .globl stage2_exception_return_sites
+    stage2_exception_return_sites:
+        .popsection
+
          /* Early tage 2 handler array setup */
         .text
     push_regs_no_ghcb:
@@ -110,6 +142,10 @@ global_asm!(
 
         movq    %rsp, %rdi
         call    stage2_generic_idt_handler_no_ghcb
+    1:
+        .pushsection .rodata
+        .long 1b - .
+        .popsection
 
         jmp generic_idt_handler_return
         
@@ -124,6 +160,8 @@ global_asm!(
         .endif
         pushq   $i  /* Vector Number */
         jmp push_regs_no_ghcb
+        /* Assembly fails if an entry outgrows its 32-byte slot */
+        .org stage2_idt_handler_array_no_ghcb + 32 * (i + 1)
         i = i + 1
         .endr
         
@@ -149,6 +187,10 @@ global_asm!(
 
         movq    %rsp, %rdi
         call    stage2_generic_idt_handler
+    1:
+        .pushsection .rodata
+        .long 1b - .
+        .popsection
 
         jmp generic_idt_handler_return
         
@@ -163,8 +205,15 @@ global_asm!(
         .endif
         pushq   $i  /* Vector Number */
         jmp push_regs_stage2
+        /* Assembly fails if an entry outgrows its 32-byte slot */
+        .org stage2_idt_handler_array + 32 * (i + 1)
         i = i + 1
         .endr
+
+        .pushsection .rodata
+        .globl stage2_exception_return_sites_end
+    stage2_exception_return_sites_end:
+        .popsection
     "#,
     options(att_syntax)
 );
@@ -15,6 +15,7 @@ use super::common::{
     DF_VECTOR, GP_VECTOR, HV_VECTOR, MCE_VECTOR, MF_VECTOR, NMI_VECTOR, NM_VECTOR, NP_VECTOR,
     OF_VECTOR, PF_VECTOR, SS_VECTOR, SX_VECTOR, TS_VECTOR, UD_VECTOR, VC_VECTOR, XF_VECTOR,
 };
+use super::ud::ud_handler;
 use crate::address::VirtAddr;
 use crate::cpu::percpu::this_cpu_unsafe;
 use crate::cpu::X86ExceptionContext;
@@ -125,6 +126,19 @@ extern "C" fn ex_handler_breakpoint(ctx: &mut X86ExceptionContext) {
     handle_debug_exception(ctx, BP_VECTOR);
 }
 
+// Invalid-Opcode handler
+#[no_mangle]
+extern "C" fn ex_handler_invalid_opcode(ctxt: &mut X86ExceptionContext) {
+    let rip = ctxt.frame.rip;
+
+    if user_mode(ctxt) {
+        log::error!("Invalid opcode at RIP {:#018x} - Terminating task", rip);
+        terminate();
+    } else if !ud_handler(ctxt) {
+        panic!("Unhandled Invalid-Opcode exception\n{}", ctxt);
+    }
+}
+
 // Doube-Fault handler
 #[no_mangle]
 extern "C" fn ex_handler_double_fault(ctxt: &mut X86ExceptionContext) {
@@ -141,10 +155,7 @@ extern "C" fn ex_handler_double_fault(ctxt: &mut X86ExceptionContext) {
         );
         terminate();
     } else {
-        panic!(
-            "Double-Fault at RIP {:#018x} RSP: {:#018x} CR2: {:#018x}",
-            rip, rsp, cr2
-        );
+        panic!("Double-Fault CR2: {:#018x}\n{}", cr2, ctxt);
     }
 }
 
@@ -161,10 +172,7 @@ extern "C" fn ex_handler_general_protection(ctxt: &mut X86ExceptionContext) {
             rip, err, rsp);
         terminate();
     } else if !handle_exception_table(ctxt) {
-        panic!(
-            "Unhandled General-Protection-Fault at RIP {:#018x} error code: {:#018x} rsp: {:#018x}",
-            rip, err, rsp
-        );
+        panic!("Unhandled General-Protection-Fault\n{}", ctxt);
     }
 }
 
@@ -196,10 +204,7 @@ extern "C" fn ex_handler_page_fault(ctxt: &mut X86ExceptionContext, vector: usiz
         && !handle_exception_table(ctxt)
     {
         handle_debug_exception(ctxt, vector);
-        panic!(
-            "Unhandled Page-Fault at RIP {:#018x} CR2: {:#018x} error code: {:#018x}",
-            rip, cr2, err
-        );
+        panic!("Unhandled Page-Fault CR2: {:#018x}\n{}", cr2, ctxt);
     }
 }
 
@@ -215,10 +220,7 @@ extern "C" fn ex_handler_vmm_communication(ctxt: &mut X86ExceptionContext, vecto
             log::error!("Failed to handle #VC from user-mode at RIP {:#018x} code: {:#018x} - Terminating task", rip, code);
             terminate();
         } else {
-            panic!(
-                "Failed to handle #VC from kernel-mode at RIP {:#018x} code: {:#018x}",
-                rip, code
-            );
+            panic!("Failed to handle #VC from kernel-mode\n{}", ctxt);
         }
     }
 }
@@ -244,14 +246,7 @@ extern "C" fn ex_handler_system_call(ctxt: &mut X86ExceptionContext) {
 
 #[no_mangle]
 pub extern "C" fn ex_handler_panic(ctx: &mut X86ExceptionContext, vector: usize) {
-    let rip = ctx.frame.rip;
-    let err = ctx.error_code;
-    let rsp = ctx.frame.rsp;
-    let ss = ctx.frame.ss;
-    panic!(
-        "Unhandled exception {} RIP {:#018x} error code: {:#018x} RSP: {:#018x} SS: {:#x}",
-        vector, rip, err, rsp, ss
-    );
+    panic!("Unhandled exception {}\n{}", vector, ctx);
 }
 
 #[no_mangle]
@@ -0,0 +1,46 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2024 SUSE LLC
+
+use super::common::X86ExceptionContext;
+use crate::address::VirtAddr;
+use crate::cpu::insn::{decode_ud, UdInstruction, MAX_INSN_SIZE};
+use crate::mm::GuestPtr;
+
+/// Tries to emulate the instruction which raised a #UD exception.
+///
+/// # Returns
+///
+/// `true` if the instruction was emulated and `ctx.frame.rip` advanced past
+/// it, `false` if the caller needs to treat the exception as fatal.
+pub fn ud_handler(ctx: &mut X86ExceptionContext) -> bool {
+    let rip = ctx.frame.rip;
+    let ptr: GuestPtr<[u8; MAX_INSN_SIZE]> = GuestPtr::new(VirtAddr::from(rip));
+    let Ok(bytes) = ptr.read() else {
+        return false;
+    };
+    let Some(insn) = decode_ud(&bytes) else {
+        return false;
+    };
+
+    let emulated = match insn {
+        // The compiler emits UD2 for aborts and traps, so it is only
+        // skipped as a breakpoint in test builds.
+        UdInstruction::Ud2 => {
+            if cfg!(test) {
+                log::info!("UD2 breakpoint at RIP {:#018x}", rip);
+            }
+            cfg!(test)
+        }
+        UdInstruction::Vmload | UdInstruction::Vmsave => {
+            // Placeholder until VMLOAD/VMSAVE proxying is implemented.
+            log::error!("{:?} emulation not implemented (RIP {:#018x})", insn, rip);
+            false
+        }
+    };
+
+    if emulated {
+        ctx.frame.rip += insn.size();
+    }
+    emulated
+}
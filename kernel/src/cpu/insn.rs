@@ -25,6 +25,8 @@ pub enum Register {
     Rdx,
     Rsp,
     Rbp,
+    Rsi,
+    Rdi,
     R8,
     R9,
     R10,
@@ -35,6 +37,32 @@ pub enum Register {
     R15,
 }
 
+impl Register {
+    /// Returns the register with the number `reg` in the instruction
+    /// encoding, including the REX extension bit.
+    const fn from_encoding(reg: u8) -> Option<Self> {
+        Some(match reg {
+            0 => Self::Rax,
+            1 => Self::Rcx,
+            2 => Self::Rdx,
+            3 => Self::Rbx,
+            4 => Self::Rsp,
+            5 => Self::Rbp,
+            6 => Self::Rsi,
+            7 => Self::Rdi,
+            8 => Self::R8,
+            9 => Self::R9,
+            10 => Self::R10,
+            11 => Self::R11,
+            12 => Self::R12,
+            13 => Self::R13,
+            14 => Self::R14,
+            15 => Self::R15,
+            _ => return None,
+        })
+    }
+}
+
 /// An operand in an instruction, which might be a register or an immediate.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum Operand {
@@ -62,6 +90,7 @@ pub enum DecodedInsn {
     Rdmsr,
     Rdtsc,
     Rdtscp,
+    Mem(MemoryInsn),
 }
 
 impl DecodedInsn {
@@ -83,10 +112,160 @@ impl DecodedInsn {
             Self::Wrmsr | Self::Rdmsr => 2,
             Self::Rdtsc => 2,
             Self::Rdtscp => 3,
+            Self::Mem(insn) => insn.rip_offset,
         }
     }
 }
 
+/// Direction of the memory access of a [`MemoryInsn`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MemoryOp {
+    /// The register is loaded from memory.
+    Read,
+    /// The register is stored to memory.
+    Write,
+    /// Memory is read, combined with the register and written back.
+    ReadModifyWrite,
+}
+
+/// How a value read from memory is widened to the register size.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Extension {
+    None,
+    Zero,
+    Sign,
+}
+
+/// An instruction accessing memory through a ModRM operand, as needed to
+/// emulate MMIO accesses.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MemoryInsn {
+    pub op: MemoryOp,
+    /// The register operand.
+    pub reg: Register,
+    /// Size of the memory access in bytes.
+    pub size: u8,
+    /// Size of the register operand in bytes. Only differs from `size` for
+    /// MOVZX and MOVSX.
+    pub reg_size: u8,
+    pub extension: Extension,
+    /// Length of the instruction.
+    pub rip_offset: usize,
+}
+
+const PREFIX_OPSIZE: u8 = 0x66;
+const REX_W: u8 = 1 << 3;
+const REX_R: u8 = 1 << 2;
+
+/// Returns the length of a ModRM byte with a memory operand and the SIB
+/// byte and displacement following it, or `None` if it encodes a register
+/// operand.
+fn modrm_len(bytes: &[u8]) -> Option<usize> {
+    let modrm = *bytes.first()?;
+    let md = modrm >> 6;
+    let rm = modrm & 7;
+    if md == 3 {
+        return None;
+    }
+
+    let mut len = 1;
+    let mut disp = match md {
+        1 => 1,
+        2 => 4,
+        _ => 0,
+    };
+    if rm == 4 {
+        let sib = *bytes.get(1)?;
+        len += 1;
+        if md == 0 && sib & 7 == 5 {
+            disp = 4;
+        }
+    } else if md == 0 && rm == 5 {
+        // RIP-relative
+        disp = 4;
+    }
+    len += disp;
+    (bytes.len() >= len).then_some(len)
+}
+
+/// Decodes a MOV, MOVZX, MOVSX, AND, OR or XOR instruction with a memory
+/// operand and a general purpose register operand.
+///
+/// # Returns
+///
+/// The decoded instruction, or `None` if the encoding is unsupported. This
+/// includes register-only forms and the legacy high byte registers (AH,
+/// CH, DH and BH).
+fn decode_memory_insn(bytes: &[u8]) -> Option<MemoryInsn> {
+    let mut pos = 0;
+    let mut opsize = false;
+    let mut rex = 0u8;
+
+    while bytes.get(pos) == Some(&PREFIX_OPSIZE) {
+        opsize = true;
+        pos += 1;
+    }
+    // A REX prefix must immediately precede the opcode.
+    if let Some(&byte @ 0x40..=0x4f) = bytes.get(pos) {
+        rex = byte;
+        pos += 1;
+    }
+
+    let full_size = if rex & REX_W != 0 {
+        8
+    } else if opsize {
+        2
+    } else {
+        4
+    };
+
+    let (op, size, reg_size, extension) = match *bytes.get(pos)? {
+        0x88 => (MemoryOp::Write, 1, 1, Extension::None),
+        0x89 => (MemoryOp::Write, full_size, full_size, Extension::None),
+        0x8a => (MemoryOp::Read, 1, 1, Extension::None),
+        0x8b => (MemoryOp::Read, full_size, full_size, Extension::None),
+        0x08 | 0x20 | 0x30 => (MemoryOp::ReadModifyWrite, 1, 1, Extension::None),
+        0x09 | 0x21 | 0x31 => (
+            MemoryOp::ReadModifyWrite,
+            full_size,
+            full_size,
+            Extension::None,
+        ),
+        0x0f => {
+            pos += 1;
+            match *bytes.get(pos)? {
+                0xb6 => (MemoryOp::Read, 1, full_size, Extension::Zero),
+                0xb7 => (MemoryOp::Read, 2, full_size, Extension::Zero),
+                0xbe => (MemoryOp::Read, 1, full_size, Extension::Sign),
+                0xbf => (MemoryOp::Read, 2, full_size, Extension::Sign),
+                _ => return None,
+            }
+        }
+        _ => return None,
+    };
+    pos += 1;
+
+    let modrm = *bytes.get(pos)?;
+    let len = modrm_len(&bytes[pos..])?;
+    let mut reg = (modrm >> 3) & 7;
+    if reg_size == 1 && rex == 0 && reg >= 4 {
+        // AH, CH, DH or BH
+        return None;
+    }
+    if rex & REX_R != 0 {
+        reg += 8;
+    }
+
+    Some(MemoryInsn {
+        op,
+        reg: Register::from_encoding(reg)?,
+        size,
+        reg_size,
+        extension,
+        rip_offset: pos + len,
+    })
+}
+
 pub const MAX_INSN_SIZE: usize = 15;
 pub const MAX_INSN_FIELD_SIZE: usize = 3;
 
@@ -101,8 +280,9 @@ impl Instruction {
 
     /// Decode the instruction.
     /// At the moment, the decoding is very naive since we only need to decode CPUID,
-    /// IN and OUT (without strings and immediate usage) instructions. A complete decoding
-    /// of the full x86 instruction set is still TODO.
+    /// IN and OUT (without strings and immediate usage) instructions, and the memory
+    /// accesses described by [`MemoryInsn`]. A complete decoding of the full x86
+    /// instruction set is still TODO.
     ///
     /// # Returns
     ///
@@ -139,6 +319,10 @@ impl Instruction {
             _ => (),
         }
 
+        if let Some(insn) = decode_memory_insn(&self.0) {
+            return Ok(DecodedInsn::Mem(insn));
+        }
+
         Err(VcError {
             rip: 0,
             code: 0,
@@ -148,10 +332,175 @@ impl Instruction {
     }
 }
 
+/// Instructions raising #UD in the SVSM which can be emulated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UdInstruction {
+    Ud2,
+    Vmload,
+    Vmsave,
+}
+
+impl UdInstruction {
+    pub const fn size(&self) -> usize {
+        match self {
+            Self::Ud2 => 2,
+            Self::Vmload | Self::Vmsave => 3,
+        }
+    }
+}
+
+/// Decode an instruction which raised a #UD exception.
+///
+/// # Returns
+///
+/// The [`UdInstruction`] if the opcode in `bytes` is recognized, `None`
+/// otherwise.
+pub fn decode_ud(bytes: &[u8]) -> Option<UdInstruction> {
+    match bytes {
+        [0x0F, 0x0B, ..] => Some(UdInstruction::Ud2),
+        [0x0F, 0x01, 0xDA, ..] => Some(UdInstruction::Vmload),
+        [0x0F, 0x01, 0xDB, ..] => Some(UdInstruction::Vmsave),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn insn(op: MemoryOp, reg: Register, size: u8, rip_offset: usize) -> MemoryInsn {
+        MemoryInsn {
+            op,
+            reg,
+            size,
+            reg_size: size,
+            extension: Extension::None,
+            rip_offset,
+        }
+    }
+
+    #[test]
+    fn test_decode_mov() {
+        // mov (%rdi), %eax
+        assert_eq!(
+            decode_memory_insn(&[0x8b, 0x07]),
+            Some(insn(MemoryOp::Read, Register::Rax, 4, 2))
+        );
+        // mov %ecx, 0x10(%rbx)
+        assert_eq!(
+            decode_memory_insn(&[0x89, 0x4b, 0x10]),
+            Some(insn(MemoryOp::Write, Register::Rcx, 4, 3))
+        );
+        // mov %dx, 0x1000(%rax)
+        assert_eq!(
+            decode_memory_insn(&[0x66, 0x89, 0x90, 0x00, 0x10, 0x00, 0x00]),
+            Some(insn(MemoryOp::Write, Register::Rdx, 2, 7))
+        );
+        // mov (%rsi), %bl
+        assert_eq!(
+            decode_memory_insn(&[0x8a, 0x1e]),
+            Some(insn(MemoryOp::Read, Register::Rbx, 1, 2))
+        );
+    }
+
+    #[test]
+    fn test_decode_mov_rex() {
+        // mov (%rax,%rcx,8), %r9
+        assert_eq!(
+            decode_memory_insn(&[0x4c, 0x8b, 0x0c, 0xc8]),
+            Some(insn(MemoryOp::Read, Register::R9, 8, 4))
+        );
+        // mov %r15d, 0x0(%rip)
+        assert_eq!(
+            decode_memory_insn(&[0x44, 0x89, 0x3d, 0x00, 0x00, 0x00, 0x00]),
+            Some(insn(MemoryOp::Write, Register::R15, 4, 7))
+        );
+        // mov %sil, (%rdi)
+        assert_eq!(
+            decode_memory_insn(&[0x40, 0x88, 0x37]),
+            Some(insn(MemoryOp::Write, Register::Rsi, 1, 3))
+        );
+        // mov %rax, 0x1234 (SIB without base)
+        assert_eq!(
+            decode_memory_insn(&[0x48, 0x89, 0x04, 0x25, 0x34, 0x12, 0x00, 0x00]),
+            Some(insn(MemoryOp::Write, Register::Rax, 8, 8))
+        );
+    }
+
+    #[test]
+    fn test_decode_movzx_movsx() {
+        // movzbl (%rdi), %eax
+        let decoded = decode_memory_insn(&[0x0f, 0xb6, 0x07]).unwrap();
+        assert_eq!(decoded.op, MemoryOp::Read);
+        assert_eq!((decoded.size, decoded.reg_size), (1, 4));
+        assert_eq!(decoded.extension, Extension::Zero);
+        assert_eq!(decoded.rip_offset, 3);
+
+        // movswq 0x8(%rbx), %r10
+        let decoded = decode_memory_insn(&[0x4c, 0x0f, 0xbf, 0x53, 0x08]).unwrap();
+        assert_eq!(decoded.reg, Register::R10);
+        assert_eq!((decoded.size, decoded.reg_size), (2, 8));
+        assert_eq!(decoded.extension, Extension::Sign);
+        assert_eq!(decoded.rip_offset, 5);
+    }
+
+    #[test]
+    fn test_decode_rmw() {
+        // and %eax, (%rdx)
+        assert_eq!(
+            decode_memory_insn(&[0x21, 0x02]),
+            Some(insn(MemoryOp::ReadModifyWrite, Register::Rax, 4, 2))
+        );
+        // or %cl, (%rdx)
+        assert_eq!(
+            decode_memory_insn(&[0x08, 0x0a]),
+            Some(insn(MemoryOp::ReadModifyWrite, Register::Rcx, 1, 2))
+        );
+        // xor %r8, 0x4(%rsp)
+        assert_eq!(
+            decode_memory_insn(&[0x4c, 0x31, 0x44, 0x24, 0x04]),
+            Some(insn(MemoryOp::ReadModifyWrite, Register::R8, 8, 5))
+        );
+    }
+
+    #[test]
+    fn test_decode_unsupported() {
+        // mov %ecx, %eax (register operand)
+        assert_eq!(decode_memory_insn(&[0x89, 0xc8]), None);
+        // mov (%rax), %ah
+        assert_eq!(decode_memory_insn(&[0x8a, 0x20]), None);
+        // truncated displacement
+        assert_eq!(decode_memory_insn(&[0x8b, 0x80, 0x00]), None);
+        // add %eax, (%rdx)
+        assert_eq!(decode_memory_insn(&[0x01, 0x02]), None);
+        assert_eq!(decode_memory_insn(&[]), None);
+    }
+
+    #[test]
+    fn test_decode_mov_instruction() {
+        let mut raw_insn = [0x41; MAX_INSN_SIZE];
+        // mov %rdi, 0x8(%rsi)
+        raw_insn[..4].copy_from_slice(&[0x48, 0x89, 0x7e, 0x08]);
+
+        let decoded = Instruction::new(raw_insn).decode().unwrap();
+        assert_eq!(
+            decoded,
+            DecodedInsn::Mem(insn(MemoryOp::Write, Register::Rdi, 8, 4))
+        );
+        assert_eq!(decoded.size(), 4);
+    }
+
+    #[test]
+    fn test_decode_ud() {
+        assert_eq!(decode_ud(&[0x0F, 0x0B, 0x90]), Some(UdInstruction::Ud2));
+        assert_eq!(decode_ud(&[0x0F, 0x01, 0xDA]), Some(UdInstruction::Vmload));
+        assert_eq!(decode_ud(&[0x0F, 0x01, 0xDB]), Some(UdInstruction::Vmsave));
+        assert_eq!(decode_ud(&[0x0F, 0x01]), None);
+        assert_eq!(decode_ud(&[0x90, 0x90, 0x90]), None);
+        assert_eq!(UdInstruction::Ud2.size(), 2);
+        assert_eq!(UdInstruction::Vmsave.size(), 3);
+    }
+
     #[test]
     fn test_decode_inw() {
         let raw_insn: [u8; MAX_INSN_SIZE] = [
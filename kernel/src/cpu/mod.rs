@@ -17,6 +17,7 @@ pub mod msr;
 pub mod percpu;
 pub mod registers;
 pub mod smp;
+pub mod snp_detect;
 pub mod tlb;
 pub mod tss;
 pub mod vc;
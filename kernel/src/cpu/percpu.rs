@@ -9,8 +9,11 @@ extern crate alloc;
 use super::gdt_mut;
 use super::tss::{X86Tss, IST_DF};
 use crate::address::{Address, PhysAddr, VirtAddr};
-use crate::cpu::ghcb::current_ghcb;
+use crate::cpu::ghcb::hv_features::hv_features;
+use crate::cpu::ghcb::{current_ghcb, GhcbPool};
 use crate::cpu::tss::TSS_LIMIT;
+#[cfg(feature = "vc-stats")]
+use crate::cpu::vc::stats::VcStats;
 use crate::cpu::vmsa::init_guest_vmsa;
 use crate::cpu::vmsa::vmsa_mut_ref_from_vaddr;
 use crate::error::SvsmError;
@@ -27,20 +30,26 @@ use crate::mm::{
 use crate::platform::SvsmPlatform;
 use crate::sev::ghcb::GHCB;
 use crate::sev::hv_doorbell::HVDoorbell;
-use crate::sev::msr_protocol::{hypervisor_ghcb_features, GHCBHvFeatures};
 use crate::sev::vmsa::allocate_new_vmsa;
 use crate::sev::RMPFlags;
 use crate::task::{schedule, schedule_task, RunQueue, Task, TaskPointer, WaitQueue};
-use crate::types::{PAGE_SHIFT, PAGE_SHIFT_2M, PAGE_SIZE, PAGE_SIZE_2M, SVSM_TR_FLAGS, SVSM_TSS};
+use crate::types::{
+    GUEST_VMPL, PAGE_SHIFT, PAGE_SHIFT_2M, PAGE_SIZE, PAGE_SIZE_2M, SVSM_TR_FLAGS, SVSM_TSS,
+};
 use crate::utils::MemoryRegion;
+#[cfg(feature = "vc-stats")]
+use alloc::boxed::Box;
 use alloc::sync::Arc;
 use alloc::vec::Vec;
-use core::cell::{Ref, RefCell, RefMut, UnsafeCell};
+use core::cell::{Cell, Ref, RefCell, RefMut, UnsafeCell};
 use core::mem::size_of;
 use core::ptr;
-use core::sync::atomic::{AtomicBool, Ordering};
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use cpuarch::vmsa::{VMSASegment, VMSA};
 
+/// Maximum number of CPUs supported by the SVSM.
+pub const MAX_CPUS: usize = 512;
+
 #[derive(Debug)]
 struct PerCpuInfo {
     apic_id: u32,
@@ -83,6 +92,18 @@ impl PerCpuAreas {
         ptr.push(info);
     }
 
+    fn len(&self) -> usize {
+        // SAFETY: see get().
+        unsafe { self.areas.get().as_ref().unwrap().len() }
+    }
+
+    /// Iterates over the shared per-CPU data of all allocated CPUs.
+    pub fn iter(&self) -> impl Iterator<Item = &'static PerCpuShared> {
+        // SAFETY: see get().
+        let ptr = unsafe { self.areas.get().as_ref().unwrap() };
+        ptr.iter().map(|info| info.cpu_shared)
+    }
+
     // Fails if no such area exists or its address is NULL
     pub fn get(&self, apic_id: u32) -> Option<&'static PerCpuShared> {
         // For this to not produce UB the only invariant we must
@@ -96,6 +117,16 @@ impl PerCpuAreas {
     }
 }
 
+/// Iterates over the shared per-CPU data of all CPUs which are online.
+pub fn online_cpus() -> impl Iterator<Item = &'static PerCpuShared> {
+    PERCPU_AREAS.iter().filter(|cpu| cpu.is_online())
+}
+
+/// Returns the number of CPUs which are online.
+pub fn cpu_count() -> usize {
+    ONLINE_CPU_COUNT.load(Ordering::Relaxed)
+}
+
 #[derive(Copy, Clone, Debug, Default)]
 pub struct VmsaRef {
     pub vaddr: VirtAddr,
@@ -200,20 +231,29 @@ impl GuestVmsaRef {
     }
 }
 
+/// Number of CPUs which called [`PerCpuShared::set_online()`].
+static ONLINE_CPU_COUNT: AtomicUsize = AtomicUsize::new(0);
+
 #[derive(Debug)]
 pub struct PerCpuShared {
+    apic_id: u32,
     guest_vmsa: SpinLock<GuestVmsaRef>,
     online: AtomicBool,
 }
 
 impl PerCpuShared {
-    fn new() -> Self {
+    fn new(apic_id: u32) -> Self {
         PerCpuShared {
+            apic_id,
             guest_vmsa: SpinLock::new(GuestVmsaRef::new()),
             online: AtomicBool::new(false),
         }
     }
 
+    pub const fn apic_id(&self) -> u32 {
+        self.apic_id
+    }
+
     pub fn update_guest_vmsa_caa(&self, vmsa: PhysAddr, caa: PhysAddr) {
         let mut locked = self.guest_vmsa.lock();
         locked.update_vmsa_caa(Some(vmsa), Some(caa));
@@ -242,7 +282,9 @@ impl PerCpuShared {
     }
 
     pub fn set_online(&self) {
-        self.online.store(true, Ordering::Release);
+        if !self.online.swap(true, Ordering::Release) {
+            ONLINE_CPU_COUNT.fetch_add(1, Ordering::Relaxed);
+        }
     }
 
     pub fn is_online(&self) -> bool {
@@ -254,7 +296,7 @@ impl PerCpuShared {
 pub struct PerCpuUnsafe {
     shared: PerCpuShared,
     private: RefCell<PerCpu>,
-    ghcb: *mut GHCB,
+    ghcb_pool: GhcbPool,
     hv_doorbell: *mut HVDoorbell,
     init_stack: Option<VirtAddr>,
     ist: IstStacks,
@@ -262,22 +304,35 @@ pub struct PerCpuUnsafe {
     /// Stack boundaries of the currently running task. This is stored in
     /// [PerCpuUnsafe] because it needs lockless read access.
     current_stack: MemoryRegion<VirtAddr>,
+
+    /// VMPL which issued the SVSM request currently being handled.
+    current_vmpl: Cell<u8>,
+
+    /// Name of the currently running task, kept here so that the panic
+    /// handler can read it without taking the run queue lock.
+    current_task_name: &'static str,
 }
 
 impl PerCpuUnsafe {
     pub fn new(apic_id: u32, cpu_unsafe_ptr: *const PerCpuUnsafe) -> Self {
         Self {
             private: RefCell::new(PerCpu::new(apic_id, cpu_unsafe_ptr)),
-            shared: PerCpuShared::new(),
-            ghcb: ptr::null_mut(),
+            shared: PerCpuShared::new(apic_id),
+            ghcb_pool: GhcbPool::new(),
             hv_doorbell: ptr::null_mut(),
             init_stack: None,
             ist: IstStacks::new(),
             current_stack: MemoryRegion::new(VirtAddr::null(), 0),
+            current_vmpl: Cell::new(GUEST_VMPL as u8),
+            current_task_name: "none",
         }
     }
 
     pub fn alloc(apic_id: u32) -> Result<*mut PerCpuUnsafe, SvsmError> {
+        if PERCPU_AREAS.len() >= MAX_CPUS {
+            return Err(SvsmError::ResourceBusy);
+        }
+
         let vaddr = allocate_zeroed_page()?;
         unsafe {
             // Within each CPU state page, the first portion is the private
@@ -309,17 +364,15 @@ impl PerCpuUnsafe {
     }
 
     pub fn setup_ghcb(&mut self) -> Result<(), SvsmError> {
-        let ghcb_page = allocate_zeroed_page().expect("Failed to allocate GHCB page");
-        if let Err(e) = GHCB::init(ghcb_page) {
-            free_page(ghcb_page);
-            return Err(e);
-        };
-        self.ghcb = ghcb_page.as_mut_ptr();
-        Ok(())
+        self.ghcb_pool.init()
+    }
+
+    pub fn ghcb_pool(&self) -> &GhcbPool {
+        &self.ghcb_pool
     }
 
     pub fn ghcb_unsafe(&self) -> *mut GHCB {
-        self.ghcb
+        self.ghcb_pool.ghcb_unsafe(0)
     }
 
     pub fn hv_doorbell_unsafe(&self) -> *mut HVDoorbell {
@@ -341,6 +394,18 @@ impl PerCpuUnsafe {
     pub fn get_current_stack(&self) -> MemoryRegion<VirtAddr> {
         self.current_stack
     }
+
+    pub fn current_task_name(&self) -> &'static str {
+        self.current_task_name
+    }
+
+    pub fn current_vmpl(&self) -> u8 {
+        self.current_vmpl.get()
+    }
+
+    pub fn set_current_vmpl(&self, vmpl: u8) {
+        self.current_vmpl.set(vmpl);
+    }
 }
 
 #[derive(Debug)]
@@ -365,6 +430,10 @@ pub struct PerCpu {
 
     /// WaitQueue for request processing
     request_waitqueue: WaitQueue,
+
+    /// #VC exit code counters
+    #[cfg(feature = "vc-stats")]
+    vc_stats: Box<VcStats>,
 }
 
 impl PerCpu {
@@ -381,6 +450,8 @@ impl PerCpu {
             vrange_2m: VirtualRange::new(),
             runqueue: RWLock::new(RunQueue::new()),
             request_waitqueue: WaitQueue::new(),
+            #[cfg(feature = "vc-stats")]
+            vc_stats: Box::default(),
         }
     }
 
@@ -404,6 +475,11 @@ impl PerCpu {
         self.apic_id
     }
 
+    #[cfg(feature = "vc-stats")]
+    pub fn vc_stats(&self) -> &VcStats {
+        &self.vc_stats
+    }
+
     fn allocate_page_table(&self) -> Result<(), SvsmError> {
         self.vm_range.initialize()?;
         let pgtable_ref = get_init_pgtable_locked().clone_shared()?;
@@ -458,10 +534,7 @@ impl PerCpu {
     }
 
     pub fn register_ghcb(&self) -> Result<(), SvsmError> {
-        unsafe {
-            let ghcb = (*self.cpu_unsafe).ghcb_unsafe();
-            ghcb.as_ref().unwrap().register()
-        }
+        unsafe { (*self.cpu_unsafe).ghcb_pool().register(0) }
     }
 
     pub fn setup_hv_doorbell(&self) -> Result<(), SvsmError> {
@@ -483,7 +556,7 @@ impl PerCpu {
     pub fn configure_hv_doorbell(&self) -> Result<(), SvsmError> {
         // #HV doorbell configuration is only required if this system will make
         // use of restricted injection.
-        if hypervisor_ghcb_features().contains(GHCBHvFeatures::SEV_SNP_RESTR_INJ) {
+        if hv_features().has_restricted_injection() {
             self.setup_hv_doorbell()
         } else {
             Ok(())
@@ -572,7 +645,7 @@ impl PerCpu {
     }
 
     pub fn setup_idle_task(&mut self, entry: extern "C" fn()) -> Result<(), SvsmError> {
-        let idle_task = Task::create(self, entry)?;
+        let idle_task = Task::create(self, entry, "idle")?;
         self.runqueue.lock_read().set_idle_task(idle_task);
         Ok(())
     }
@@ -594,12 +667,8 @@ impl PerCpu {
 
     pub fn shutdown(&mut self) -> Result<(), SvsmError> {
         unsafe {
-            let ghcb = (*self.cpu_unsafe).ghcb_unsafe();
-            if ghcb.is_null() {
-                return Ok(());
-            }
-
-            (*ghcb).shutdown()
+            let cpu_unsafe_mut_ptr = self.cpu_unsafe as *mut PerCpuUnsafe;
+            (*cpu_unsafe_mut_ptr).ghcb_pool.shutdown()
         }
     }
 
@@ -738,6 +807,7 @@ impl PerCpu {
         unsafe {
             let cpu_unsafe_mut_ptr = self.cpu_unsafe as *mut PerCpuUnsafe;
             (*cpu_unsafe_mut_ptr).current_stack = task.stack_bounds();
+            (*cpu_unsafe_mut_ptr).current_task_name = task.name();
         }
         task
     }
@@ -748,6 +818,7 @@ impl PerCpu {
             unsafe {
                 let cpu_unsafe_mut_ptr = self.cpu_unsafe as *mut PerCpuUnsafe;
                 (*cpu_unsafe_mut_ptr).current_stack = next.stack_bounds();
+                (*cpu_unsafe_mut_ptr).current_task_name = next.name();
             }
         };
         ret
@@ -774,6 +845,16 @@ pub fn this_cpu_shared() -> &'static PerCpuShared {
     unsafe { (*this_cpu_unsafe()).shared() }
 }
 
+/// Returns the name of the task running on this CPU.
+pub fn current_task_name() -> &'static str {
+    unsafe { (*this_cpu_unsafe()).current_task_name() }
+}
+
+/// Returns the VMPL which issued the SVSM request being handled on this CPU.
+pub fn this_cpu_vmpl() -> u8 {
+    unsafe { (*this_cpu_unsafe()).current_vmpl() }
+}
+
 pub fn this_cpu() -> Ref<'static, PerCpu> {
     let cpu_unsafe = unsafe { &*this_cpu_unsafe() };
     cpu_unsafe.cpu()
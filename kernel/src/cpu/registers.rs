@@ -4,6 +4,8 @@
 //
 // Author: Roy Hopkins <rhopkins@suse.de>
 
+use core::fmt;
+
 #[repr(C, packed)]
 #[derive(Default, Debug, Clone, Copy)]
 pub struct X86GeneralRegs {
@@ -24,6 +26,43 @@ pub struct X86GeneralRegs {
     pub rax: usize,
 }
 
+impl fmt::Display for X86GeneralRegs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Fields are copied out with braces as the struct is packed.
+        writeln!(
+            f,
+            "RAX: {:#018x}  RBX: {:#018x}  RCX: {:#018x}  RDX: {:#018x}",
+            { self.rax },
+            { self.rbx },
+            { self.rcx },
+            { self.rdx }
+        )?;
+        writeln!(
+            f,
+            "RSI: {:#018x}  RDI: {:#018x}  RBP: {:#018x}  R8:  {:#018x}",
+            { self.rsi },
+            { self.rdi },
+            { self.rbp },
+            { self.r8 }
+        )?;
+        writeln!(
+            f,
+            "R9:  {:#018x}  R10: {:#018x}  R11: {:#018x}  R12: {:#018x}",
+            { self.r9 },
+            { self.r10 },
+            { self.r11 },
+            { self.r12 }
+        )?;
+        write!(
+            f,
+            "R13: {:#018x}  R14: {:#018x}  R15: {:#018x}",
+            { self.r13 },
+            { self.r14 },
+            { self.r15 }
+        )
+    }
+}
+
 #[repr(C, packed)]
 #[derive(Default, Debug, Clone, Copy)]
 pub struct X86SegmentRegs {
@@ -6,6 +6,7 @@
 
 use crate::acpi::tables::ACPICPUInfo;
 use crate::cpu::ghcb::current_ghcb;
+use crate::cpu::ghcb::hv_features::hv_features;
 use crate::cpu::percpu::{this_cpu, this_cpu_mut, this_cpu_shared, PerCpu};
 use crate::cpu::vmsa::init_svsm_vmsa;
 use crate::platform::SvsmPlatform;
@@ -50,6 +51,10 @@ fn start_cpu(platform: &dyn SvsmPlatform, apic_id: u32, vtom: u64) {
 }
 
 pub fn start_secondary_cpus(platform: &dyn SvsmPlatform, cpus: &[ACPICPUInfo], vtom: u64) {
+    if !hv_features().has_ap_create() {
+        log::warn!("Hypervisor does not advertise SNP AP creation, AP startup may fail");
+    }
+
     immut_after_init_set_multithreaded();
     let mut count: usize = 0;
     for c in cpus.iter().filter(|c| c.apic_id != 0 && c.enabled) {
@@ -86,7 +91,8 @@ fn start_ap() {
 
 #[no_mangle]
 pub extern "C" fn ap_request_loop() {
-    create_kernel_task(request_processing_main).expect("Failed to launch request processing task");
+    create_kernel_task(request_processing_main, "request-processing")
+        .expect("Failed to launch request processing task");
     request_loop();
     panic!("Returned from request_loop!");
 }
@@ -0,0 +1,126 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2024 SUSE LLC
+
+//! Detection of the SEV features reported in CPUID leaf 0x8000001F.
+
+use crate::sev::status::sev_snp_enabled;
+
+use super::cpuid::{cpuid_table, CpuidResult};
+
+const CPUID_MEMORY_ENCRYPTION_INFO: u32 = 0x8000_001f;
+
+// CPUID 0x8000001F EAX feature bits
+const X86_FEATURE_SEV: u32 = 1;
+const X86_FEATURE_SEV_ES: u32 = 3;
+const X86_FEATURE_SEV_SNP: u32 = 4;
+const X86_FEATURE_VMPL: u32 = 5;
+const X86_FEATURE_RESTRICTED_INJECTION: u32 = 12;
+const X86_FEATURE_ALTERNATE_INJECTION: u32 = 13;
+
+/// SEV features supported by the platform.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SnpFeatures {
+    pub sev: bool,
+    pub sev_es: bool,
+    pub snp: bool,
+    pub vmpl: bool,
+    pub restricted_injection: bool,
+    pub alternate_injection: bool,
+    /// Number of VMPLs supported
+    pub vmpl_count: u8,
+}
+
+impl SnpFeatures {
+    fn from_cpuid(c: &CpuidResult) -> Self {
+        let has = |bit: u32| (c.eax >> bit) & 1 == 1;
+        Self {
+            sev: has(X86_FEATURE_SEV),
+            sev_es: has(X86_FEATURE_SEV_ES),
+            snp: has(X86_FEATURE_SEV_SNP),
+            vmpl: has(X86_FEATURE_VMPL),
+            restricted_injection: has(X86_FEATURE_RESTRICTED_INJECTION),
+            alternate_injection: has(X86_FEATURE_ALTERNATE_INJECTION),
+            vmpl_count: ((c.ebx >> 12) & 0xf) as u8,
+        }
+    }
+}
+
+/// Reads the SEV features from the CPUID table. Returns `None` if the table
+/// has no entry for leaf 0x8000001F.
+pub fn detect_snp_features() -> Option<SnpFeatures> {
+    cpuid_table(CPUID_MEMORY_ENCRYPTION_INFO).map(|c| SnpFeatures::from_cpuid(&c))
+}
+
+/// Returns the number of VMPLs supported by the platform, or `None` if the
+/// CPUID table does not report it.
+pub fn vmpl_count() -> Option<u8> {
+    detect_snp_features().map(|features| features.vmpl_count)
+}
+
+/// Checks that the platform supports SEV-SNP with enough VMPLs to run a
+/// guest at `guest_vmpl` below the SVSM.
+///
+/// Whether SEV-SNP is active is decided by the SEV_STATUS MSR. A CPUID
+/// table that does not report SEV-SNP, or has no entry for leaf 0x8000001F
+/// at all, cannot be trusted for the VMPL count and only causes a warning.
+///
+/// # Panics
+///
+/// Panics if SEV-SNP is not active, or if CPUID reports SEV-SNP with fewer
+/// VMPLs than needed.
+pub fn snp_features_verify(guest_vmpl: u8) {
+    if !sev_snp_enabled() {
+        log::error!("SEV-SNP is not active (SEV_STATUS)");
+        panic!("SEV-SNP not active");
+    }
+
+    let Some(features) = detect_snp_features() else {
+        log::warn!("CPUID table has no leaf 0x8000001F, VMPL support not checked");
+        return;
+    };
+
+    if !features.snp {
+        log::warn!("CPUID 0x8000001F does not report SEV-SNP, VMPL support not checked");
+        return;
+    }
+
+    if !features.vmpl || features.vmpl_count <= guest_vmpl {
+        log::error!(
+            "Platform supports {} VMPL(s), the SVSM requires at least {}",
+            features.vmpl_count,
+            guest_vmpl + 1
+        );
+        panic!("Not enough VMPLs supported");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snp_features_from_cpuid() {
+        // SME, SEV, SEV-ES, SNP, VMPL, restricted and alternate injection
+        // with 4 VMPLs
+        let c = CpuidResult {
+            eax: 0x0101_fd3f,
+            ebx: 0x0000_4173,
+            ecx: 0,
+            edx: 0,
+        };
+        let features = SnpFeatures::from_cpuid(&c);
+        assert!(features.sev && features.sev_es && features.snp && features.vmpl);
+        assert!(features.restricted_injection && features.alternate_injection);
+        assert_eq!(features.vmpl_count, 4);
+
+        let none = SnpFeatures::from_cpuid(&CpuidResult {
+            eax: 0x1,
+            ebx: 0,
+            ecx: 0,
+            edx: 0,
+        });
+        assert!(!none.sev && !none.snp && !none.vmpl);
+        assert_eq!(none.vmpl_count, 0);
+    }
+}
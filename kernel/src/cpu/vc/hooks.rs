@@ -0,0 +1,159 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2024 SUSE LLC
+
+//! Registration of optional #VC handlers. Hooks are consulted by
+//! [`handle_vc_exception()`](super::handle_vc_exception) before the
+//! built-in dispatch, in registration order.
+
+use crate::cpu::idt::common::X86ExceptionContext;
+use crate::error::SvsmError;
+use crate::locking::RWLock;
+
+/// Maximum number of hooks that can be registered at the same time.
+pub const MAX_VC_HOOKS: usize = 32;
+
+/// Outcome of a #VC hook invocation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HookResult {
+    /// The hook handled the exception, including advancing RIP if needed.
+    /// No further handlers are consulted.
+    Handled,
+    /// The hook did not handle the exception.
+    NotHandled,
+}
+
+pub type VcHook = fn(&mut X86ExceptionContext) -> HookResult;
+
+/// Handle returned by [`register_vc_hook()`], used to unregister the hook.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HookId(u64);
+
+#[derive(Clone, Copy, Debug)]
+struct VcHookEntry {
+    id: HookId,
+    exit_code: u64,
+    hook: VcHook,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct VcHookTable {
+    entries: [Option<VcHookEntry>; MAX_VC_HOOKS],
+    len: usize,
+    next_id: u64,
+}
+
+impl VcHookTable {
+    const fn new() -> Self {
+        Self {
+            entries: [None; MAX_VC_HOOKS],
+            len: 0,
+            next_id: 0,
+        }
+    }
+
+    fn insert(&mut self, exit_code: u64, hook: VcHook) -> Result<HookId, SvsmError> {
+        if self.len == MAX_VC_HOOKS {
+            return Err(SvsmError::ResourceBusy);
+        }
+
+        let id = HookId(self.next_id);
+        self.next_id += 1;
+        self.entries[self.len] = Some(VcHookEntry {
+            id,
+            exit_code,
+            hook,
+        });
+        self.len += 1;
+        Ok(id)
+    }
+
+    fn remove(&mut self, id: HookId) {
+        let Some(idx) = self.entries[..self.len]
+            .iter()
+            .position(|e| e.is_some_and(|entry| entry.id == id))
+        else {
+            return;
+        };
+
+        // Shift the remaining entries down to keep registration order.
+        self.entries.copy_within(idx + 1..self.len, idx);
+        self.len -= 1;
+        self.entries[self.len] = None;
+    }
+}
+
+static VC_HOOKS: RWLock<VcHookTable> = RWLock::new(VcHookTable::new());
+
+/// Registers `hook` to be called for every #VC exception with the given
+/// exit code.
+///
+/// # Returns
+///
+/// The [`HookId`] of the new hook, or [`SvsmError::ResourceBusy`] if
+/// [`MAX_VC_HOOKS`] hooks are already registered.
+pub fn register_vc_hook(exit_code: u64, hook: VcHook) -> Result<HookId, SvsmError> {
+    VC_HOOKS.lock_write().insert(exit_code, hook)
+}
+
+/// Unregisters a hook previously registered with [`register_vc_hook()`].
+/// Unknown ids are ignored.
+pub fn unregister_vc_hook(id: HookId) {
+    VC_HOOKS.lock_write().remove(id);
+}
+
+/// Calls the hooks registered for the exit code of `ctx` until one of them
+/// reports [`HookResult::Handled`].
+pub fn run_vc_hooks(ctx: &mut X86ExceptionContext) -> HookResult {
+    // Take a copy so the lock is not held while the hooks run, as they may
+    // raise nested #VC exceptions.
+    let table = *VC_HOOKS.lock_read();
+    let exit_code = ctx.error_code as u64;
+
+    for entry in table.entries[..table.len].iter().flatten() {
+        if entry.exit_code == exit_code && (entry.hook)(ctx) == HookResult::Handled {
+            return HookResult::Handled;
+        }
+    }
+
+    HookResult::NotHandled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn handled(_ctx: &mut X86ExceptionContext) -> HookResult {
+        HookResult::Handled
+    }
+
+    #[test]
+    fn test_hook_table_full() {
+        let mut table = VcHookTable::new();
+        for _ in 0..MAX_VC_HOOKS {
+            table.insert(0x72, handled).unwrap();
+        }
+        assert!(matches!(
+            table.insert(0x72, handled),
+            Err(SvsmError::ResourceBusy)
+        ));
+    }
+
+    #[test]
+    fn test_hook_table_remove_keeps_order() {
+        let mut table = VcHookTable::new();
+        let a = table.insert(1, handled).unwrap();
+        let b = table.insert(2, handled).unwrap();
+        let c = table.insert(3, handled).unwrap();
+
+        table.remove(b);
+        assert_eq!(table.len, 2);
+        assert_eq!(table.entries[0].unwrap().id, a);
+        assert_eq!(table.entries[1].unwrap().id, c);
+        assert!(table.entries[2].is_none());
+
+        // Removing an unknown id is a no-op.
+        table.remove(b);
+        assert_eq!(table.len, 2);
+    }
+}
@@ -4,6 +4,10 @@
 //
 // Author: Joerg Roedel <jroedel@suse.de>
 
+pub mod hooks;
+#[cfg(feature = "vc-stats")]
+pub mod stats;
+
 use super::idt::common::X86ExceptionContext;
 use super::insn::MAX_INSN_SIZE;
 use crate::address::Address;
@@ -18,6 +22,7 @@ use crate::error::SvsmError;
 use crate::mm::GuestPtr;
 use crate::sev::ghcb::{GHCBIOSize, GHCB};
 use core::fmt;
+use hooks::{run_vc_hooks, HookResult};
 
 pub const SVM_EXIT_EXCP_BASE: usize = 0x40;
 pub const SVM_EXIT_LAST_EXCP: usize = 0x5f;
@@ -122,6 +127,13 @@ pub fn stage2_handle_vc_exception(ctx: &mut X86ExceptionContext) -> Result<(), S
 }
 
 pub fn handle_vc_exception(ctx: &mut X86ExceptionContext, vector: usize) -> Result<(), SvsmError> {
+    #[cfg(feature = "vc-stats")]
+    this_cpu().vc_stats().record(ctx.error_code);
+
+    if run_vc_hooks(ctx) == HookResult::Handled {
+        return Ok(());
+    }
+
     let error_code = ctx.error_code;
 
     // To handle NAE events, we're supposed to reset the VALID_BITMAP field of
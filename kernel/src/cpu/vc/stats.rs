@@ -0,0 +1,91 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2024 SUSE LLC
+
+//! Per-CPU counters of #VC exceptions by exit code.
+
+use crate::cpu::percpu::this_cpu;
+use core::sync::atomic::{AtomicU64, Ordering};
+
+/// Number of exit codes tracked. Exit codes beyond this range are not
+/// counted.
+pub const VC_STATS_EXIT_CODES: usize = 256;
+
+/// Number of entries printed by [`dump_vc_stats()`].
+const VC_STATS_DUMP_ENTRIES: usize = 20;
+
+#[derive(Debug)]
+pub struct VcStats {
+    counts: [AtomicU64; VC_STATS_EXIT_CODES],
+}
+
+impl VcStats {
+    pub const fn new() -> Self {
+        Self {
+            counts: [const { AtomicU64::new(0) }; VC_STATS_EXIT_CODES],
+        }
+    }
+
+    pub fn record(&self, exit_code: usize) {
+        if let Some(count) = self.counts.get(exit_code) {
+            count.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn count(&self, exit_code: usize) -> u64 {
+        self.counts
+            .get(exit_code)
+            .map_or(0, |count| count.load(Ordering::Relaxed))
+    }
+
+    /// Returns the non-zero counters as `(exit_code, count)` pairs, most
+    /// frequent first.
+    fn sorted(&self) -> [(usize, u64); VC_STATS_EXIT_CODES] {
+        let mut entries = [(0usize, 0u64); VC_STATS_EXIT_CODES];
+        for (code, entry) in entries.iter_mut().enumerate() {
+            *entry = (code, self.count(code));
+        }
+        entries.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        entries
+    }
+}
+
+impl Default for VcStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Logs the most frequent #VC exit codes seen on the current CPU.
+pub fn dump_vc_stats() {
+    let cpu = this_cpu();
+    let entries = cpu.vc_stats().sorted();
+
+    log::info!("#VC statistics for CPU {}:", cpu.get_apic_id());
+    for (code, count) in entries
+        .iter()
+        .take(VC_STATS_DUMP_ENTRIES)
+        .filter(|(_, count)| *count != 0)
+    {
+        log::info!("  exit code {:#04x}: {}", code, count);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vc_stats_sorted() {
+        let stats = VcStats::new();
+        stats.record(0x72);
+        stats.record(0x7b);
+        stats.record(0x7b);
+        stats.record(VC_STATS_EXIT_CODES);
+
+        let entries = stats.sorted();
+        assert_eq!(entries[0], (0x7b, 2));
+        assert_eq!(entries[1], (0x72, 1));
+        assert_eq!(entries[2].1, 0);
+    }
+}
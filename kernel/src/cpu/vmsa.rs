@@ -12,7 +12,7 @@ use cpuarch::vmsa::{VMSASegment, VMSA};
 use super::control_regs::{read_cr0, read_cr3, read_cr4};
 use super::efer::read_efer;
 use super::gdt;
-use super::idt::common::idt;
+use super::idt::common::{idt, GP_VECTOR, NP_VECTOR, PF_VECTOR, UD_VECTOR};
 
 fn svsm_code_segment() -> VMSASegment {
     VMSASegment {
@@ -115,6 +115,23 @@ pub fn vmsa_mut_ref_from_vaddr(vaddr: VirtAddr) -> &'static mut VMSA {
     unsafe { vaddr.as_mut_ptr::<VMSA>().as_mut().unwrap() }
 }
 
+/// Logs the state in `vmsa`, which belongs to the CPU with APIC ID
+/// `apic_id`, to help debugging requests that could not be handled.
+///
+/// The console is visible to the hypervisor, and guest registers may hold
+/// secrets. Only RIP, the exit code and the VMPL are logged as errors; the
+/// full register state is logged at debug level.
+pub fn dump_vmsa(vmsa: &VMSA, apic_id: u32) {
+    log::error!(
+        "VMSA of CPU {}, VMPL {}: RIP {:#018x}, exit code {:?}",
+        apic_id,
+        { vmsa.vmpl },
+        { vmsa.rip },
+        { vmsa.guest_exit_code }
+    );
+    log::debug!("VMSA of CPU {}:\n{}", apic_id, vmsa);
+}
+
 pub fn init_guest_vmsa(v: &mut VMSA, rip: u64) {
     v.cr0 = 0x6000_0010;
     v.rflags = 0x2;
@@ -145,3 +162,140 @@ pub fn init_guest_vmsa(v: &mut VMSA, rip: u64) {
     sev_status.remove(SEVStatusFlags::REST_INJ);
     v.sev_features = sev_status.as_sev_features();
 }
+
+// EVENTINJ field layout (APM Vol. 2, 15.20 Event Injection)
+const EVENT_INJ_VECTOR_MASK: u64 = 0xff;
+const EVENT_INJ_TYPE_SHIFT: u64 = 8;
+const EVENT_INJ_TYPE_MASK: u64 = 7 << EVENT_INJ_TYPE_SHIFT;
+const EVENT_INJ_EV: u64 = 1 << 11;
+const EVENT_INJ_VALID: u64 = 1 << 31;
+const EVENT_INJ_ERROR_CODE_SHIFT: u64 = 32;
+
+/// Type of an injected event.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EventType {
+    Interrupt = 0,
+    Nmi = 2,
+    Exception = 3,
+    SoftwareInterrupt = 4,
+}
+
+impl EventType {
+    const fn from_bits(bits: u64) -> Option<Self> {
+        match bits {
+            0 => Some(Self::Interrupt),
+            2 => Some(Self::Nmi),
+            3 => Some(Self::Exception),
+            4 => Some(Self::SoftwareInterrupt),
+            _ => None,
+        }
+    }
+}
+
+/// An event to be delivered to the guest through the EVENTINJ field of a
+/// VMSA.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EventInjection {
+    pub vector: u8,
+    pub event_type: EventType,
+    pub error_code: Option<u32>,
+}
+
+impl EventInjection {
+    /// Encodes the event for the EVENTINJ field.
+    ///
+    /// ```
+    /// # use svsm::cpu::vmsa::build_exception;
+    /// // #UD, no error code
+    /// assert_eq!(build_exception(6, None).to_raw(), 0x8000_0306);
+    /// // #GP with selector error code 0x10
+    /// assert_eq!(build_exception(13, Some(0x10)).to_raw(), 0x0000_0010_8000_0b0d);
+    /// // #PF with error code 0x2 (write)
+    /// assert_eq!(build_exception(14, Some(0x2)).to_raw(), 0x0000_0002_8000_0b0e);
+    /// // #NP with selector error code 0x28
+    /// assert_eq!(build_exception(11, Some(0x28)).to_raw(), 0x0000_0028_8000_0b0b);
+    /// ```
+    pub const fn to_raw(&self) -> u64 {
+        let event = EVENT_INJ_VALID
+            | ((self.event_type as u64) << EVENT_INJ_TYPE_SHIFT)
+            | self.vector as u64;
+        match self.error_code {
+            Some(code) => event | EVENT_INJ_EV | ((code as u64) << EVENT_INJ_ERROR_CODE_SHIFT),
+            None => event,
+        }
+    }
+
+    /// Decodes an EVENTINJ value. Returns `None` if no valid event is
+    /// encoded.
+    ///
+    /// ```
+    /// # use svsm::cpu::vmsa::{build_exception, EventInjection};
+    /// let gp = build_exception(13, Some(0));
+    /// assert_eq!(EventInjection::from_raw(gp.to_raw()), Some(gp));
+    /// assert_eq!(EventInjection::from_raw(0x0306), None);
+    /// ```
+    pub const fn from_raw(raw: u64) -> Option<Self> {
+        if raw & EVENT_INJ_VALID == 0 {
+            return None;
+        }
+        let Some(event_type) =
+            EventType::from_bits((raw & EVENT_INJ_TYPE_MASK) >> EVENT_INJ_TYPE_SHIFT)
+        else {
+            return None;
+        };
+        let error_code = if raw & EVENT_INJ_EV != 0 {
+            Some((raw >> EVENT_INJ_ERROR_CODE_SHIFT) as u32)
+        } else {
+            None
+        };
+        Some(Self {
+            vector: (raw & EVENT_INJ_VECTOR_MASK) as u8,
+            event_type,
+            error_code,
+        })
+    }
+}
+
+/// Builds an exception event with an optional error code.
+pub const fn build_exception(vector: u8, error_code: Option<u32>) -> EventInjection {
+    EventInjection {
+        vector,
+        event_type: EventType::Exception,
+        error_code,
+    }
+}
+
+/// Queues `event` for injection into the guest, replacing any event that
+/// is still pending.
+pub fn vmsa_inject_event(v: &mut VMSA, event: EventInjection) {
+    v.event_inj = event.to_raw();
+}
+
+/// Returns the event currently queued for injection, if any.
+pub fn vmsa_pending_event(v: &VMSA) -> Option<EventInjection> {
+    EventInjection::from_raw(v.event_inj)
+}
+
+/// Queues a #GP with the given selector error code for injection into
+/// the guest.
+pub fn vmsa_inject_gp(v: &mut VMSA, selector: u16) {
+    vmsa_inject_event(v, build_exception(GP_VECTOR as u8, Some(selector.into())));
+}
+
+/// Queues a #UD for injection into the guest.
+pub fn vmsa_inject_ud(v: &mut VMSA) {
+    vmsa_inject_event(v, build_exception(UD_VECTOR as u8, None));
+}
+
+/// Queues a #PF for injection into the guest and sets the guest CR2 to
+/// the faulting address.
+pub fn vmsa_inject_pf(v: &mut VMSA, cr2: VirtAddr, error_code: u32) {
+    v.cr2 = cr2.bits() as u64;
+    vmsa_inject_event(v, build_exception(PF_VECTOR as u8, Some(error_code)));
+}
+
+/// Queues a #NP with the given selector error code for injection into
+/// the guest.
+pub fn vmsa_inject_np(v: &mut VMSA, selector: u16) {
+    vmsa_inject_event(v, build_exception(NP_VECTOR as u8, Some(selector.into())));
+}
@@ -77,6 +77,23 @@ pub mod aead {
     pub struct Aes256Gcm;
 }
 
+pub mod digest {
+    //! API for message digests
+
+    /// SHA-384 digest size (384 bits)
+    pub const SHA384_DIGEST_SIZE: usize = 48;
+
+    /// SHA-384
+    pub trait Sha384Trait {
+        /// Compute the SHA-384 digest of the concatenation of `parts`
+        fn digest(parts: &[&[u8]]) -> [u8; SHA384_DIGEST_SIZE];
+    }
+
+    /// Sha384 type
+    #[derive(Copy, Clone, Debug)]
+    pub struct Sha384;
+}
+
 // Crypto implementations supported. Only one of them must be compiled-in.
 
 pub mod rustcrypto;
@@ -10,11 +10,15 @@ use aes_gcm::{
     aead::{Aead, Payload},
     Aes256Gcm, Key, KeyInit, Nonce,
 };
+use sha2::{Digest, Sha384};
 
 use crate::{
     crypto::aead::{
         Aes256Gcm as CryptoAes256Gcm, Aes256GcmTrait as CryptoAes256GcmTrait, IV_SIZE, KEY_SIZE,
     },
+    crypto::digest::{
+        Sha384 as CryptoSha384, Sha384Trait as CryptoSha384Trait, SHA384_DIGEST_SIZE,
+    },
     protocols::errors::SvsmReqError,
 };
 
@@ -75,3 +79,15 @@ impl CryptoAes256GcmTrait for CryptoAes256Gcm {
         aes_gcm_do(AesGcmOperation::Decrypt, iv, key, aad, inbuf, outbuf)
     }
 }
+
+impl CryptoSha384Trait for CryptoSha384 {
+    fn digest(parts: &[&[u8]]) -> [u8; SHA384_DIGEST_SIZE] {
+        let mut hasher = Sha384::new();
+        for part in parts {
+            hasher.update(part);
+        }
+        let mut digest = [0u8; SHA384_DIGEST_SIZE];
+        digest.copy_from_slice(&hasher.finalize());
+        digest
+    }
+}
@@ -23,6 +23,7 @@ pub mod svsm_gdbstub {
     use crate::serial::{SerialPort, Terminal};
     use crate::svsm_console::SVSMIOPort;
     use crate::task::{is_current_task, TaskContext, INITIAL_TASK_ID, TASKLIST};
+    use crate::utils::once::Once;
     use core::arch::asm;
     use core::fmt;
     use core::sync::atomic::{AtomicBool, Ordering};
@@ -56,7 +57,7 @@ pub mod svsm_gdbstub {
                 .run_state_machine(&mut target)
                 .expect("Failed to start GDB state machine");
             *GDB_STATE.lock() = Some(SvsmGdbStub { gdb, target });
-            GDB_STACK_TOP = GDB_STACK.as_mut_ptr().offset(GDB_STACK.len() as isize - 1) as u64;
+            GDB_STACK_TOP.init(GDB_STACK.as_mut_ptr().offset(GDB_STACK.len() as isize - 1) as u64);
         }
         GDB_INITIALISED.store(true, Ordering::Relaxed);
         Ok(())
@@ -133,7 +134,7 @@ pub mod svsm_gdbstub {
                     in("rsi") exception_type as u64,
                     in("rdi") &mut task_ctx,
                     in("rdx") &mut gdb_state,
-                    in("rax") GDB_STACK_TOP,
+                    in("rax") *GDB_STACK_TOP.get(),
                     options(att_syntax));
             }
 
@@ -178,7 +179,7 @@ pub mod svsm_gdbstub {
     static mut PACKET_BUFFER: [u8; 4096] = [0; 4096];
     // Allocate the GDB stack as an array of u64's to ensure 8 byte alignment of the stack.
     static mut GDB_STACK: [u64; 8192] = [0; 8192];
-    static mut GDB_STACK_TOP: u64 = 0;
+    static GDB_STACK_TOP: Once<u64> = Once::new();
 
     struct GdbTaskContext {
         cr3: usize,
@@ -29,7 +29,36 @@ enum UnwoundStackFrame {
     Invalid,
 }
 
-type StacksBounds = [MemoryRegion<VirtAddr>; 3];
+const MAX_STACKS: usize = 8;
+
+/// Default number of return addresses recorded by [`capture_stack()`].
+pub const BACKTRACE_DEPTH: usize = 16;
+
+/// The stacks the unwinder is allowed to walk.
+#[derive(Clone, Copy, Debug)]
+struct StacksBounds {
+    stacks: [MemoryRegion<VirtAddr>; MAX_STACKS],
+    active_count: usize,
+}
+
+impl StacksBounds {
+    fn new() -> Self {
+        Self {
+            stacks: [MemoryRegion::from_addresses(VirtAddr::null(), VirtAddr::null()); MAX_STACKS],
+            active_count: 0,
+        }
+    }
+
+    fn push(&mut self, stack: MemoryRegion<VirtAddr>) {
+        assert!(self.active_count < MAX_STACKS, "Too many stacks to unwind");
+        self.stacks[self.active_count] = stack;
+        self.active_count += 1;
+    }
+
+    fn iter(&self) -> impl Iterator<Item = &MemoryRegion<VirtAddr>> {
+        self.stacks[..self.active_count].iter()
+    }
+}
 
 #[derive(Debug)]
 struct StackUnwinder {
@@ -54,11 +83,16 @@ impl StackUnwinder {
             )
         };
 
-        let stacks: StacksBounds = [
-            MemoryRegion::from_addresses(top_of_init_stack - STACK_SIZE, top_of_init_stack),
-            MemoryRegion::from_addresses(top_of_df_stack - STACK_SIZE, top_of_df_stack),
-            current_stack,
-        ];
+        let mut stacks = StacksBounds::new();
+        stacks.push(MemoryRegion::from_addresses(
+            top_of_init_stack - STACK_SIZE,
+            top_of_init_stack,
+        ));
+        stacks.push(MemoryRegion::from_addresses(
+            top_of_df_stack - STACK_SIZE,
+            top_of_df_stack,
+        ));
+        stacks.push(current_stack);
 
         Self::new(VirtAddr::from(rbp), stacks)
     }
@@ -116,6 +150,8 @@ impl StackUnwinder {
             return UnwoundStackFrame::Invalid;
         }
 
+        // The frame pointer chain is not trusted to be properly aligned, so
+        // read the saved RBP and return address unaligned.
         let rbp = unsafe { rsp.as_ptr::<VirtAddr>().read_unaligned() };
         let rsp = rsp + mem::size_of::<VirtAddr>();
         let rip = unsafe { rsp.as_ptr::<VirtAddr>().read_unaligned() };
@@ -134,7 +170,9 @@ impl StackUnwinder {
             return UnwoundStackFrame::Invalid;
         }
 
-        let ctx = unsafe { &*rsp.as_ptr::<X86ExceptionContext>() };
+        let Some(ctx) = (unsafe { rsp.as_aligned_ref::<X86ExceptionContext>() }) else {
+            return UnwoundStackFrame::Invalid;
+        };
         let rbp = VirtAddr::from(ctx.regs.rbp);
         let rip = VirtAddr::from(ctx.frame.rip);
         let rsp = VirtAddr::from(ctx.frame.rsp);
@@ -142,6 +180,25 @@ impl StackUnwinder {
         Self::check_unwound_frame(rbp, rsp, rip, stacks)
     }
 
+    /// Records the RIPs of up to `N` frames after skipping the first `skip`
+    /// ones. Stops at the first frame that cannot be unwound.
+    ///
+    /// # Returns
+    ///
+    /// The RIPs, and how many entries of the array are filled
+    fn collect_rips<const N: usize>(&mut self, skip: usize) -> ([VirtAddr; N], usize) {
+        let mut rips = [VirtAddr::null(); N];
+        let mut count = 0;
+        for frame in self.by_ref().skip(skip).take(N) {
+            let UnwoundStackFrame::Valid(frame) = frame else {
+                break;
+            };
+            rips[count] = frame.rip;
+            count += 1;
+        }
+        (rips, count)
+    }
+
     fn frame_is_last(rbp: VirtAddr) -> bool {
         // A new task is launched with RBP = 0, which is pushed onto the stack
         // immediatly and can serve as a marker when the end of the stack has
@@ -181,6 +238,12 @@ impl Iterator for StackUnwinder {
     }
 }
 
+/// Captures the call stack of the current CPU for later reporting, see
+/// [`StackUnwinder::collect_rips()`].
+pub fn capture_stack<const N: usize>(skip: usize) -> ([VirtAddr; N], usize) {
+    StackUnwinder::unwind_this_cpu().collect_rips(skip)
+}
+
 pub fn print_stack(skip: usize) {
     let unwinder = StackUnwinder::unwind_this_cpu();
     log::info!("---BACKTRACE---:");
@@ -0,0 +1,231 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2024 SUSE LLC
+
+//! Register model of a single-timer HPET whose main counter is derived
+//! from the TSC.
+
+use crate::cpu::msr::rdtsc;
+use crate::error::SvsmError;
+use crate::locking::SpinLock;
+
+/// Guest physical address at which QEMU/KVM places the HPET.
+pub const HPET_BASE: u64 = 0xfed0_0000;
+/// Size of the HPET MMIO register block.
+pub const HPET_MMIO_SIZE: usize = 0x400;
+
+/// Counter period of the emulated HPET in femtoseconds (100 MHz).
+pub const HPET_PERIOD_FS: u64 = 10_000_000;
+
+const FS_PER_SEC: u128 = 1_000_000_000_000_000;
+
+// Register offsets
+const HPET_GCAP_ID: u64 = 0x000;
+const HPET_GEN_CONF: u64 = 0x010;
+const HPET_GINTR_STA: u64 = 0x020;
+const HPET_MAIN_CNT: u64 = 0x0f0;
+const HPET_TIM0_CONF: u64 = 0x100;
+const HPET_TIM0_COMP: u64 = 0x108;
+
+const GCAP_REV_ID: u64 = 0x01;
+const GCAP_COUNT_SIZE_CAP: u64 = 1 << 13;
+const GCAP_LEG_RT_CAP: u64 = 1 << 15;
+const GCAP_VENDOR_ID: u64 = 0x8086 << 16;
+
+const GEN_CONF_ENABLE: u64 = 1 << 0;
+const GEN_CONF_LEG_RT: u64 = 1 << 1;
+
+const TIM_CONF_INT_TYPE: u64 = 1 << 1;
+const TIM_CONF_INT_ENB: u64 = 1 << 2;
+const TIM_CONF_TYPE: u64 = 1 << 3;
+const TIM_CONF_PER_INT_CAP: u64 = 1 << 4;
+const TIM_CONF_SIZE_CAP: u64 = 1 << 5;
+const TIM_CONF_VAL_SET: u64 = 1 << 6;
+const TIM_CONF_WRITABLE: u64 = TIM_CONF_INT_TYPE | TIM_CONF_INT_ENB | TIM_CONF_TYPE;
+
+#[derive(Debug)]
+struct HpetRegs {
+    config: u64,
+    intr_status: u64,
+    /// Counter value at `tsc_start`, or the frozen value while halted.
+    counter_base: u64,
+    tsc_start: u64,
+    tim0_config: u64,
+    tim0_comparator: u64,
+}
+
+#[derive(Debug)]
+pub struct HpetEmulator {
+    tsc_hz: u64,
+    regs: SpinLock<HpetRegs>,
+}
+
+impl HpetEmulator {
+    /// Creates a halted HPET. `tsc_hz` is the TSC frequency used to derive
+    /// the main counter. Fails with [`SvsmError::InvalidParameter`] if
+    /// `tsc_hz` is zero.
+    pub const fn new(tsc_hz: u64) -> Result<Self, SvsmError> {
+        if tsc_hz == 0 {
+            return Err(SvsmError::InvalidParameter);
+        }
+        Ok(Self {
+            tsc_hz,
+            regs: SpinLock::new(HpetRegs {
+                config: 0,
+                intr_status: 0,
+                counter_base: 0,
+                tsc_start: 0,
+                tim0_config: 0,
+                tim0_comparator: u64::MAX,
+            }),
+        })
+    }
+
+    fn ticks(&self, tsc_delta: u64) -> u64 {
+        let fs = u128::from(tsc_delta) * FS_PER_SEC / u128::from(self.tsc_hz);
+        (fs / u128::from(HPET_PERIOD_FS)) as u64
+    }
+
+    fn counter(&self, regs: &HpetRegs, tsc: u64) -> u64 {
+        if regs.config & GEN_CONF_ENABLE == 0 {
+            regs.counter_base
+        } else {
+            regs.counter_base
+                .wrapping_add(self.ticks(tsc.wrapping_sub(regs.tsc_start)))
+        }
+    }
+
+    /// Reads from the register at `offset`. Registers are 64 bits wide and
+    /// may also be read with 32-bit accesses, so a read at the upper half of
+    /// a register returns that half in the low 32 bits.
+    fn mmio_read_at(&self, offset: u64, tsc: u64) -> u64 {
+        let regs = self.regs.lock();
+        let val = match offset & !7 {
+            HPET_GCAP_ID => {
+                (HPET_PERIOD_FS << 32)
+                    | GCAP_VENDOR_ID
+                    | GCAP_LEG_RT_CAP
+                    | GCAP_COUNT_SIZE_CAP
+                    | GCAP_REV_ID
+            }
+            HPET_GEN_CONF => regs.config,
+            HPET_GINTR_STA => regs.intr_status,
+            HPET_MAIN_CNT => self.counter(&regs, tsc),
+            HPET_TIM0_CONF => regs.tim0_config | TIM_CONF_PER_INT_CAP | TIM_CONF_SIZE_CAP,
+            HPET_TIM0_COMP => regs.tim0_comparator,
+            _ => 0,
+        };
+        if offset & 4 != 0 {
+            val >> 32
+        } else {
+            val
+        }
+    }
+
+    fn mmio_write_at(&self, offset: u64, val: u64, tsc: u64) {
+        let mut regs = self.regs.lock();
+        match offset {
+            HPET_GEN_CONF => {
+                let was_enabled = regs.config & GEN_CONF_ENABLE != 0;
+                let enable = val & GEN_CONF_ENABLE != 0;
+                if was_enabled && !enable {
+                    regs.counter_base = self.counter(&regs, tsc);
+                } else if !was_enabled && enable {
+                    regs.tsc_start = tsc;
+                }
+                regs.config = val & (GEN_CONF_ENABLE | GEN_CONF_LEG_RT);
+            }
+            // Status bits are cleared by writing 1.
+            HPET_GINTR_STA => regs.intr_status &= !val,
+            // The main counter may only be written while it is halted.
+            HPET_MAIN_CNT if regs.config & GEN_CONF_ENABLE == 0 => regs.counter_base = val,
+            HPET_TIM0_CONF => {
+                regs.tim0_config = val & (TIM_CONF_WRITABLE | TIM_CONF_VAL_SET);
+            }
+            HPET_TIM0_COMP => {
+                regs.tim0_comparator = val;
+                regs.tim0_config &= !TIM_CONF_VAL_SET;
+            }
+            _ => {}
+        }
+    }
+
+    /// Emulates a 32-bit or 64-bit read from the register at `offset`
+    /// within the MMIO block. Reads from unimplemented registers return
+    /// zero.
+    pub fn handle_mmio_read(&self, offset: u64) -> u64 {
+        self.mmio_read_at(offset, rdtsc())
+    }
+
+    /// Emulates a 64-bit write to the register at `offset` within the
+    /// MMIO block. Writes to read-only or unimplemented registers are
+    /// ignored.
+    pub fn handle_mmio_write(&self, offset: u64, val: u64) {
+        self.mmio_write_at(offset, val, rdtsc())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 1 GHz TSC, so 10 TSC cycles make one HPET tick
+    const TSC_HZ: u64 = 1_000_000_000;
+
+    #[test]
+    fn test_hpet_capabilities() {
+        let hpet = HpetEmulator::new(TSC_HZ).unwrap();
+        let gcap = hpet.mmio_read_at(HPET_GCAP_ID, 0);
+        assert_eq!(gcap >> 32, HPET_PERIOD_FS);
+        assert_ne!(gcap & GCAP_COUNT_SIZE_CAP, 0);
+        assert_eq!((gcap >> 8) & 0x1f, 0);
+    }
+
+    #[test]
+    fn test_hpet_32bit_reads() {
+        let hpet = HpetEmulator::new(TSC_HZ).unwrap();
+        assert_eq!(hpet.mmio_read_at(HPET_GCAP_ID + 4, 0), HPET_PERIOD_FS);
+        assert_eq!(hpet.mmio_read_at(HPET_GCAP_ID, 0) as u32 >> 16, 0x8086);
+
+        hpet.mmio_write_at(HPET_MAIN_CNT, 0x1_0000_0002, 0);
+        assert_eq!(hpet.mmio_read_at(HPET_MAIN_CNT, 0) as u32, 2);
+        assert_eq!(hpet.mmio_read_at(HPET_MAIN_CNT + 4, 0), 1);
+    }
+
+    #[test]
+    fn test_hpet_zero_tsc_hz() {
+        assert!(HpetEmulator::new(0).is_err());
+    }
+
+    #[test]
+    fn test_hpet_main_counter() {
+        let hpet = HpetEmulator::new(TSC_HZ).unwrap();
+
+        // Halted counter is writable and does not advance.
+        hpet.mmio_write_at(HPET_MAIN_CNT, 100, 0);
+        assert_eq!(hpet.mmio_read_at(HPET_MAIN_CNT, 5000), 100);
+
+        hpet.mmio_write_at(HPET_GEN_CONF, GEN_CONF_ENABLE, 1000);
+        assert_eq!(hpet.mmio_read_at(HPET_MAIN_CNT, 2000), 200);
+
+        // Writes are ignored while running.
+        hpet.mmio_write_at(HPET_MAIN_CNT, 0, 2000);
+        assert_eq!(hpet.mmio_read_at(HPET_MAIN_CNT, 3000), 300);
+
+        hpet.mmio_write_at(HPET_GEN_CONF, 0, 3000);
+        assert_eq!(hpet.mmio_read_at(HPET_MAIN_CNT, 9000), 300);
+    }
+
+    #[test]
+    fn test_hpet_timer0() {
+        let hpet = HpetEmulator::new(TSC_HZ).unwrap();
+        hpet.mmio_write_at(HPET_TIM0_CONF, !0, 0);
+        let conf = hpet.mmio_read_at(HPET_TIM0_CONF, 0);
+        assert_ne!(conf & TIM_CONF_INT_ENB, 0);
+        assert_ne!(conf & TIM_CONF_VAL_SET, 0);
+
+        hpet.mmio_write_at(HPET_TIM0_COMP, 0x1234, 0);
+        assert_eq!(hpet.mmio_read_at(HPET_TIM0_COMP, 0), 0x1234);
+        assert_eq!(hpet.mmio_read_at(HPET_TIM0_CONF, 0) & TIM_CONF_VAL_SET, 0);
+    }
+}
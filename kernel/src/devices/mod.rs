@@ -0,0 +1,8 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2024 SUSE LLC
+
+pub mod hpet;
+pub mod pit;
+pub mod vga_text;
+pub mod virtio_coalescing;
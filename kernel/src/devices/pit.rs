@@ -0,0 +1,297 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2024 SUSE LLC
+
+//! Register model of the 8254 programmable interval timer whose counters
+//! are derived from the TSC.
+
+use crate::cpu::msr::rdtsc;
+use crate::error::SvsmError;
+use crate::locking::SpinLock;
+
+/// Nominal input clock of the PIT in Hz.
+pub const PIT_FREQ_HZ: u64 = 1_193_182;
+
+pub const PIT_CHANNEL0_PORT: u16 = 0x40;
+pub const PIT_CHANNEL1_PORT: u16 = 0x41;
+pub const PIT_CHANNEL2_PORT: u16 = 0x42;
+pub const PIT_MODE_PORT: u16 = 0x43;
+
+const PIT_CHANNELS: usize = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum AccessMode {
+    LowByte,
+    HighByte,
+    LowHigh,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct PitChannel {
+    mode: u8,
+    access: AccessMode,
+    /// Counter reload value, a programmed count of zero means 65536.
+    reload: u32,
+    /// Whether a complete count has been written since the last mode
+    /// change.
+    armed: bool,
+    start_tsc: u64,
+    latched: Option<u16>,
+    read_high: bool,
+    write_high: bool,
+    write_low: u8,
+}
+
+impl PitChannel {
+    const fn new() -> Self {
+        Self {
+            mode: 0,
+            access: AccessMode::LowHigh,
+            reload: 0x10000,
+            armed: false,
+            start_tsc: 0,
+            latched: None,
+            read_high: false,
+            write_high: false,
+            write_low: 0,
+        }
+    }
+
+    fn load(&mut self, count: u16, tsc: u64) {
+        self.reload = if count == 0 { 0x10000 } else { count.into() };
+        self.armed = true;
+        self.start_tsc = tsc;
+    }
+}
+
+#[derive(Debug)]
+pub struct Pit8254 {
+    tsc_hz: u64,
+    channels: SpinLock<[PitChannel; PIT_CHANNELS]>,
+}
+
+impl Pit8254 {
+    /// Creates a PIT whose counters advance with a TSC running at
+    /// `tsc_hz`. Fails with [`SvsmError::InvalidParameter`] if `tsc_hz` is
+    /// zero.
+    pub const fn new(tsc_hz: u64) -> Result<Self, SvsmError> {
+        if tsc_hz == 0 {
+            return Err(SvsmError::InvalidParameter);
+        }
+        Ok(Self {
+            tsc_hz,
+            channels: SpinLock::new([PitChannel::new(); PIT_CHANNELS]),
+        })
+    }
+
+    fn elapsed(&self, ch: &PitChannel, tsc: u64) -> u64 {
+        let delta = u128::from(tsc.wrapping_sub(ch.start_tsc));
+        (delta * u128::from(PIT_FREQ_HZ) / u128::from(self.tsc_hz)) as u64
+    }
+
+    fn count(&self, ch: &PitChannel, tsc: u64) -> u16 {
+        if !ch.armed {
+            return ch.reload as u16;
+        }
+        let reload = u64::from(ch.reload);
+        let elapsed = self.elapsed(ch, tsc);
+        let count = match ch.mode {
+            // Rate generator: counts from the reload value down to 1.
+            2 => reload - elapsed % reload,
+            // Square wave: decrements by two for each input clock.
+            3 => reload - (elapsed * 2) % reload,
+            // One-shot modes keep counting down and wrap around.
+            _ => reload.wrapping_sub(elapsed),
+        };
+        count as u16
+    }
+
+    /// Returns the state of the OUT pin of `channel` at `tsc`, or `None` if
+    /// there is no such channel.
+    fn output_at(&self, channel: usize, tsc: u64) -> Option<bool> {
+        let channels = self.channels.lock();
+        let ch = channels.get(channel)?;
+        if !ch.armed {
+            return Some(ch.mode != 0);
+        }
+        let reload = u64::from(ch.reload);
+        let elapsed = self.elapsed(ch, tsc);
+        let out = match ch.mode {
+            // Interrupt on terminal count: goes high once the count expires.
+            0 | 1 => elapsed >= reload,
+            2 => elapsed % reload != reload - 1,
+            3 => (elapsed * 2) % (2 * reload) < reload,
+            // Strobe modes pulse low for one clock at terminal count.
+            _ => elapsed != reload,
+        };
+        Some(out)
+    }
+
+    fn io_read_at(&self, port: u16, tsc: u64) -> u8 {
+        let idx = match port {
+            PIT_CHANNEL0_PORT..=PIT_CHANNEL2_PORT => usize::from(port - PIT_CHANNEL0_PORT),
+            _ => return 0xff,
+        };
+        let mut channels = self.channels.lock();
+        let count = channels[idx]
+            .latched
+            .unwrap_or_else(|| self.count(&channels[idx], tsc));
+        let ch = &mut channels[idx];
+
+        let high = match ch.access {
+            AccessMode::LowByte => false,
+            AccessMode::HighByte => true,
+            AccessMode::LowHigh => {
+                ch.read_high = !ch.read_high;
+                !ch.read_high
+            }
+        };
+        // A latched value is released once it has been read completely.
+        if high || ch.access == AccessMode::LowByte {
+            ch.latched = None;
+        }
+
+        if high {
+            (count >> 8) as u8
+        } else {
+            count as u8
+        }
+    }
+
+    fn io_write_at(&self, port: u16, val: u8, tsc: u64) {
+        let mut channels = self.channels.lock();
+        match port {
+            PIT_CHANNEL0_PORT..=PIT_CHANNEL2_PORT => {
+                let ch = &mut channels[usize::from(port - PIT_CHANNEL0_PORT)];
+                match ch.access {
+                    AccessMode::LowByte => ch.load(val.into(), tsc),
+                    AccessMode::HighByte => ch.load(u16::from(val) << 8, tsc),
+                    AccessMode::LowHigh if !ch.write_high => {
+                        ch.write_low = val;
+                        ch.write_high = true;
+                    }
+                    AccessMode::LowHigh => {
+                        ch.load(u16::from(ch.write_low) | (u16::from(val) << 8), tsc);
+                        ch.write_high = false;
+                    }
+                }
+            }
+            PIT_MODE_PORT => {
+                let idx = usize::from(val >> 6);
+                // Read-back commands are not supported.
+                if idx >= PIT_CHANNELS {
+                    return;
+                }
+                let access = match (val >> 4) & 3 {
+                    0 => {
+                        // Counter latch command
+                        let count = self.count(&channels[idx], tsc);
+                        channels[idx].latched.get_or_insert(count);
+                        return;
+                    }
+                    1 => AccessMode::LowByte,
+                    2 => AccessMode::HighByte,
+                    _ => AccessMode::LowHigh,
+                };
+                // Modes 6 and 7 are aliases of modes 2 and 3.
+                let mode = match (val >> 1) & 7 {
+                    m @ 6..=7 => m - 4,
+                    m => m,
+                };
+                channels[idx] = PitChannel {
+                    mode,
+                    access,
+                    ..PitChannel::new()
+                };
+            }
+            _ => {}
+        }
+    }
+
+    /// Emulates an 8-bit read from one of the PIT I/O ports.
+    pub fn io_read(&self, port: u16) -> u8 {
+        self.io_read_at(port, rdtsc())
+    }
+
+    /// Emulates an 8-bit write to one of the PIT I/O ports.
+    pub fn io_write(&self, port: u16, val: u8) {
+        self.io_write_at(port, val, rdtsc())
+    }
+
+    /// Returns the state of the OUT pin of `channel`, which would drive
+    /// IRQ0 for channel 0. Returns `None` if `channel` is not 0, 1 or 2.
+    pub fn output(&self, channel: usize) -> Option<bool> {
+        self.output_at(channel, rdtsc())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TSC_HZ: u64 = 2_000_000_000;
+
+    const fn ms_to_tsc(ms: u64) -> u64 {
+        TSC_HZ / 1000 * ms
+    }
+
+    fn read_count(pit: &Pit8254, port: u16, tsc: u64) -> u16 {
+        let lo = pit.io_read_at(port, tsc);
+        let hi = pit.io_read_at(port, tsc);
+        u16::from(lo) | (u16::from(hi) << 8)
+    }
+
+    #[test]
+    fn test_pit_delay_10ms() {
+        // Channel 0, lobyte/hibyte, mode 0, as used by BIOS delay loops.
+        let pit = Pit8254::new(TSC_HZ).unwrap();
+        let count: u16 = (PIT_FREQ_HZ / 100) as u16;
+        pit.io_write_at(PIT_MODE_PORT, 0x30, 0);
+        pit.io_write_at(PIT_CHANNEL0_PORT, count as u8, 0);
+        pit.io_write_at(PIT_CHANNEL0_PORT, (count >> 8) as u8, 0);
+
+        assert_eq!(pit.output_at(0, ms_to_tsc(5)), Some(false));
+        let half = read_count(&pit, PIT_CHANNEL0_PORT, ms_to_tsc(5));
+        assert!(half > count / 2 - 2 && half < count / 2 + 2);
+
+        assert_eq!(
+            pit.output_at(0, ms_to_tsc(10) + ms_to_tsc(1) / 10),
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn test_pit_output_invalid_channel() {
+        let pit = Pit8254::new(TSC_HZ).unwrap();
+        assert_eq!(pit.output_at(3, 0), None);
+    }
+
+    #[test]
+    fn test_pit_zero_tsc_hz() {
+        assert!(Pit8254::new(0).is_err());
+    }
+
+    #[test]
+    fn test_pit_latch() {
+        let pit = Pit8254::new(TSC_HZ).unwrap();
+        pit.io_write_at(PIT_MODE_PORT, 0x34, 0);
+        pit.io_write_at(PIT_CHANNEL0_PORT, 0x00, 0);
+        pit.io_write_at(PIT_CHANNEL0_PORT, 0x10, 0);
+
+        pit.io_write_at(PIT_MODE_PORT, 0x00, ms_to_tsc(1));
+        let latched = read_count(&pit, PIT_CHANNEL0_PORT, ms_to_tsc(2));
+        assert_eq!(latched, read_count(&pit, PIT_CHANNEL0_PORT, ms_to_tsc(1)));
+    }
+
+    #[test]
+    fn test_pit_rate_generator() {
+        let pit = Pit8254::new(TSC_HZ).unwrap();
+        pit.io_write_at(PIT_MODE_PORT, 0x14, 0);
+        pit.io_write_at(PIT_CHANNEL0_PORT, 100, 0);
+
+        for ms in 0..5 {
+            let count = pit.io_read_at(PIT_CHANNEL0_PORT, ms_to_tsc(ms));
+            assert!((1..=100).contains(&count));
+        }
+    }
+}
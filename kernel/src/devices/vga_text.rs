@@ -0,0 +1,202 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2024 SUSE LLC
+
+//! Output to a VGA text-mode framebuffer.
+
+use crate::address::VirtAddr;
+use crate::io::IOPort;
+use core::fmt;
+
+/// Guest physical address of the VGA text-mode framebuffer.
+pub const VGA_TEXT_FRAMEBUFFER: u64 = 0xb8000;
+pub const VGA_TEXT_COLS: usize = 80;
+pub const VGA_TEXT_ROWS: usize = 25;
+/// Size of the framebuffer in bytes.
+pub const VGA_TEXT_SIZE: usize = VGA_TEXT_COLS * VGA_TEXT_ROWS * 2;
+
+const CRTC_INDEX_PORT: u16 = 0x3d4;
+const CRTC_DATA_PORT: u16 = 0x3d5;
+const CRTC_CURSOR_HIGH: u8 = 0x0e;
+const CRTC_CURSOR_LOW: u8 = 0x0f;
+
+/// Light grey on black.
+const VGA_DEFAULT_ATTR: u16 = 0x07 << 8;
+
+#[derive(Debug)]
+pub struct VgaTextConsole {
+    framebuffer: VirtAddr,
+    cursor_row: u8,
+    cursor_col: u8,
+    io: &'static dyn IOPort,
+}
+
+impl VgaTextConsole {
+    /// Creates a console writing to the framebuffer mapped at `framebuffer`.
+    ///
+    /// # Safety
+    ///
+    /// `framebuffer` must point to a writable mapping of at least
+    /// [`VGA_TEXT_SIZE`] bytes which stays valid for the lifetime of the
+    /// console. To be visible to the hypervisor the mapping must be shared.
+    pub unsafe fn new(framebuffer: VirtAddr, io: &'static dyn IOPort) -> Self {
+        Self {
+            framebuffer,
+            cursor_row: 0,
+            cursor_col: 0,
+            io,
+        }
+    }
+
+    pub fn cursor(&self) -> (u8, u8) {
+        (self.cursor_row, self.cursor_col)
+    }
+
+    fn cell(&self, row: usize, col: usize) -> *mut u16 {
+        debug_assert!(row < VGA_TEXT_ROWS && col < VGA_TEXT_COLS);
+        self.framebuffer
+            .as_mut_ptr::<u16>()
+            .wrapping_add(row * VGA_TEXT_COLS + col)
+    }
+
+    fn write_cell(&mut self, row: usize, col: usize, ch: u8) {
+        // SAFETY: the cell lies within the framebuffer, which the caller of
+        // new() guaranteed to be mapped and writable.
+        unsafe {
+            self.cell(row, col)
+                .write_volatile(VGA_DEFAULT_ATTR | u16::from(ch))
+        };
+    }
+
+    fn clear_row(&mut self, row: usize) {
+        for col in 0..VGA_TEXT_COLS {
+            self.write_cell(row, col, b' ');
+        }
+    }
+
+    /// Clears the screen and moves the cursor to the top-left corner.
+    pub fn clear(&mut self) {
+        for row in 0..VGA_TEXT_ROWS {
+            self.clear_row(row);
+        }
+        self.cursor_row = 0;
+        self.cursor_col = 0;
+        self.update_cursor();
+    }
+
+    fn scroll(&mut self) {
+        for row in 1..VGA_TEXT_ROWS {
+            for col in 0..VGA_TEXT_COLS {
+                // SAFETY: both cells lie within the framebuffer.
+                unsafe {
+                    let val = self.cell(row, col).read_volatile();
+                    self.cell(row - 1, col).write_volatile(val);
+                }
+            }
+        }
+        self.clear_row(VGA_TEXT_ROWS - 1);
+    }
+
+    fn newline(&mut self) {
+        self.cursor_col = 0;
+        if usize::from(self.cursor_row) + 1 < VGA_TEXT_ROWS {
+            self.cursor_row += 1;
+        } else {
+            self.scroll();
+        }
+    }
+
+    fn put_byte(&mut self, ch: u8) {
+        match ch {
+            b'\n' => self.newline(),
+            b'\r' => self.cursor_col = 0,
+            _ => {
+                let (row, col) = (usize::from(self.cursor_row), usize::from(self.cursor_col));
+                self.write_cell(row, col, ch);
+                if col + 1 < VGA_TEXT_COLS {
+                    self.cursor_col += 1;
+                } else {
+                    self.newline();
+                }
+            }
+        }
+    }
+
+    /// Moves the hardware cursor to the current position through the CRTC
+    /// registers.
+    fn update_cursor(&self) {
+        let pos = usize::from(self.cursor_row) * VGA_TEXT_COLS + usize::from(self.cursor_col);
+        self.io.outb(CRTC_INDEX_PORT, CRTC_CURSOR_HIGH);
+        self.io.outb(CRTC_DATA_PORT, (pos >> 8) as u8);
+        self.io.outb(CRTC_INDEX_PORT, CRTC_CURSOR_LOW);
+        self.io.outb(CRTC_DATA_PORT, pos as u8);
+    }
+}
+
+impl fmt::Write for VgaTextConsole {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for ch in s.bytes() {
+            self.put_byte(ch);
+        }
+        self.update_cursor();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::locking::SpinLock;
+    use core::fmt::Write;
+
+    #[derive(Debug)]
+    struct TestIOPort {
+        cursor: SpinLock<(u8, u16)>,
+    }
+
+    impl IOPort for TestIOPort {
+        fn outb(&self, port: u16, value: u8) {
+            let mut state = self.cursor.lock();
+            match port {
+                CRTC_INDEX_PORT => state.0 = value,
+                CRTC_DATA_PORT if state.0 == CRTC_CURSOR_HIGH => {
+                    state.1 = (state.1 & 0xff) | (u16::from(value) << 8)
+                }
+                CRTC_DATA_PORT if state.0 == CRTC_CURSOR_LOW => {
+                    state.1 = (state.1 & 0xff00) | u16::from(value)
+                }
+                _ => {}
+            }
+        }
+    }
+
+    static TEST_IO: TestIOPort = TestIOPort {
+        cursor: SpinLock::new((0, 0)),
+    };
+
+    #[test]
+    fn test_vga_text_write_and_scroll() {
+        let mut fb = [0u16; VGA_TEXT_COLS * VGA_TEXT_ROWS];
+        let mut console = unsafe { VgaTextConsole::new(VirtAddr::from(fb.as_mut_ptr()), &TEST_IO) };
+        console.clear();
+
+        write!(console, "ab\r").unwrap();
+        write!(console, "c\nd").unwrap();
+        assert_eq!(console.cursor(), (1, 1));
+        // Hardware cursor at row 1, column 1.
+        assert_eq!(TEST_IO.cursor.lock().1, 81);
+
+        for _ in 0..VGA_TEXT_ROWS {
+            writeln!(console).unwrap();
+        }
+        write!(console, "e").unwrap();
+        assert_eq!(console.cursor(), (VGA_TEXT_ROWS as u8 - 1, 1));
+        drop(console);
+
+        assert_eq!(fb[0] & 0xff, u16::from(b' '));
+        assert_eq!(
+            fb[(VGA_TEXT_ROWS - 1) * VGA_TEXT_COLS] & 0xff,
+            u16::from(b'e')
+        );
+    }
+}
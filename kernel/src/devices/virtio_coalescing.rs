@@ -0,0 +1,132 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2024 SUSE LLC
+
+//! Coalescing of used buffer notifications of emulated VirtIO queues.
+
+use crate::cpu::msr::rdtsc;
+
+/// Decides when the guest is notified about completed requests of a queue.
+///
+/// The guest is notified once `threshold` completions are pending, or when
+/// [`InterruptCoalescer::should_interrupt()`] is polled at least
+/// `interval` after the oldest pending completion.
+/// By default every completion is notified immediately.
+#[derive(Debug)]
+pub struct InterruptCoalescer {
+    pending: u32,
+    threshold: u32,
+    interval_tsc: u64,
+    /// TSC at which the oldest pending completion was accounted for.
+    first_pending_tsc: u64,
+}
+
+impl InterruptCoalescer {
+    pub const fn new() -> Self {
+        Self {
+            pending: 0,
+            threshold: 1,
+            interval_tsc: 0,
+            first_pending_tsc: 0,
+        }
+    }
+
+    /// Sets the number of completions to collect and the maximum time in
+    /// microseconds to delay a notification. `tsc_hz` is the TSC frequency
+    /// used to convert the interval. A `threshold` of zero is treated as
+    /// one.
+    pub fn configure(&mut self, threshold: u32, interval_us: u32, tsc_hz: u64) {
+        self.threshold = threshold.max(1);
+        let interval_tsc = u128::from(interval_us) * u128::from(tsc_hz) / 1_000_000;
+        self.interval_tsc = u64::try_from(interval_tsc).unwrap_or(u64::MAX);
+    }
+
+    fn should_interrupt_at(&mut self, tsc: u64) -> bool {
+        if self.pending == 0 {
+            return false;
+        }
+
+        let expired = self.interval_tsc != 0
+            && tsc.saturating_sub(self.first_pending_tsc) >= self.interval_tsc;
+        if self.pending < self.threshold && !expired {
+            return false;
+        }
+
+        self.pending = 0;
+        true
+    }
+
+    fn complete_at(&mut self, tsc: u64) -> bool {
+        if self.pending == 0 {
+            self.first_pending_tsc = tsc;
+        }
+        self.pending = self.pending.saturating_add(1);
+        self.should_interrupt_at(tsc)
+    }
+
+    /// Accounts for one completed request.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the guest should be notified now.
+    pub fn complete(&mut self) -> bool {
+        self.complete_at(rdtsc())
+    }
+
+    /// Checks whether delayed completions should be notified now. Needs to
+    /// be polled for the interval to take effect.
+    pub fn should_interrupt(&mut self) -> bool {
+        self.should_interrupt_at(rdtsc())
+    }
+}
+
+impl Default for InterruptCoalescer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_coalescing_default_immediate() {
+        let mut coalescer = InterruptCoalescer::new();
+        assert!(!coalescer.should_interrupt_at(0));
+        assert!(coalescer.complete_at(0));
+        assert!(coalescer.complete_at(0));
+        assert!(!coalescer.should_interrupt_at(1));
+    }
+
+    #[test]
+    fn test_coalescing_threshold_and_interval() {
+        let mut coalescer = InterruptCoalescer::new();
+        // 100us at 1 GHz
+        coalescer.configure(3, 100, 1_000_000_000);
+
+        assert!(!coalescer.complete_at(10));
+        assert!(!coalescer.complete_at(20));
+        assert!(coalescer.complete_at(30));
+
+        // The interval starts with the first pending completion.
+        assert!(!coalescer.complete_at(40));
+        assert!(!coalescer.complete_at(50_000));
+        assert!(!coalescer.should_interrupt_at(100_039));
+        assert!(coalescer.should_interrupt_at(100_040));
+        assert!(!coalescer.should_interrupt_at(300_000));
+    }
+
+    #[test]
+    fn test_coalescing_nonzero_start_tsc() {
+        let mut coalescer = InterruptCoalescer::new();
+        coalescer.configure(3, 100, 1_000_000_000);
+
+        // A TSC far from zero does not expire the first interval.
+        let start = 5_000_000_000_000;
+        assert!(!coalescer.complete_at(start));
+        assert!(!coalescer.complete_at(start + 10));
+        assert!(!coalescer.should_interrupt_at(start + 99_999));
+        assert!(coalescer.should_interrupt_at(start + 100_000));
+    }
+}
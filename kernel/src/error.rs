@@ -47,6 +47,8 @@ pub enum SvsmError {
     MissingSecrets,
     /// Invalid address, usually provided by the guest
     InvalidAddress,
+    /// Invalid parameter or guest state, usually provided by the guest
+    InvalidParameter,
     /// Errors related to firmware parsing
     Firmware,
     /// Errors related to firmware configuration contents
@@ -59,4 +61,8 @@ pub enum SvsmError {
     Task(TaskError),
     /// Errors from #VC handler
     Vc(VcError),
+    /// A fixed-capacity resource has no free slots left.
+    ResourceBusy,
+    /// An operation did not complete before its deadline.
+    Timeout,
 }
@@ -366,7 +366,7 @@ fn validate_fw_mem_region(
     let pstart = region.start();
     let pend = region.end();
 
-    log::info!("Validating {:#018x}-{:#018x}", pstart, pend);
+    log::info!("Validating {}-{}", pstart, pend);
 
     if config.page_state_change_required() {
         current_ghcb()
@@ -405,10 +405,9 @@ fn validate_fw_memory_vec(
     let mut region = regions[0];
 
     for next in regions.into_iter().skip(1) {
-        if region.contiguous(&next) {
-            region = region.merge(&next);
-        } else {
-            next_vec.push(next);
+        match region.try_merge(&next) {
+            Some(merged) => region = merged,
+            None => next_vec.push(next),
         }
     }
 
@@ -12,29 +12,66 @@ extern crate alloc;
 
 use alloc::boxed::Box;
 use core::ptr::addr_of_mut;
+use core::sync::atomic::{AtomicU64, Ordering};
 use core::{cell::OnceCell, mem::size_of};
 
 use crate::{
     address::VirtAddr,
-    cpu::ghcb::current_ghcb,
+    cpu::ghcb::{hv_features::hv_features, GhcbGuard},
+    cpu::msr::rdtsc,
     error::SvsmError,
     greq::msg::{SnpGuestRequestExtData, SnpGuestRequestMsg, SnpGuestRequestMsgType},
     locking::SpinLock,
     protocols::errors::{SvsmReqError, SvsmResultCode},
     sev::{ghcb::GhcbError, secrets_page, secrets_page_mut, VMPCK_SIZE},
     types::PAGE_SHIFT,
-    BIT,
 };
 
 /// Global `SNP_GUEST_REQUEST` driver instance
 static GREQ_DRIVER: SpinLock<OnceCell<SnpGuestRequestDriver>> = SpinLock::new(OnceCell::new());
 
-// Hypervisor error codes
+/// TSC frequency assumed for the default timeout. The SVSM has no trusted
+/// source for the real frequency: AMD CPUs do not report CPUID leaf 0x15,
+/// and the guest TSC frequency MSR is only available with Secure TSC, which
+/// the SVSM does not enable. On a faster TSC the default timeout is shorter,
+/// on a slower one it is longer.
+const GREQ_ASSUMED_TSC_HZ: u64 = 2_000_000_000;
 
-/// Buffer provided is too small
-const SNP_GUEST_REQ_INVALID_LEN: u64 = BIT!(32);
-/// Hypervisor busy, try again
-const SNP_GUEST_REQ_ERR_BUSY: u64 = BIT!(33);
+/// Default number of TSC cycles to keep resending a request the hypervisor
+/// reports as busy, about five seconds at [`GREQ_ASSUMED_TSC_HZ`]. Use
+/// [`set_guest_request_timeout()`] to set an exact number of cycles.
+const GREQ_DEFAULT_TIMEOUT_CYCLES: u64 = 5 * GREQ_ASSUMED_TSC_HZ;
+
+static GREQ_TIMEOUT_CYCLES: AtomicU64 = AtomicU64::new(GREQ_DEFAULT_TIMEOUT_CYCLES);
+
+/// Set the number of TSC cycles after which a busy `SNP_GUEST_REQUEST` is
+/// abandoned.
+pub fn set_guest_request_timeout(cycles: u64) {
+    GREQ_TIMEOUT_CYCLES.store(cycles, Ordering::Relaxed);
+}
+
+/// Returns a TSC deadline for an `SNP_GUEST_REQUEST` sent now, based on the
+/// configured timeout.
+pub fn guest_request_deadline() -> u64 {
+    rdtsc().saturating_add(GREQ_TIMEOUT_CYCLES.load(Ordering::Relaxed))
+}
+
+fn is_hv_busy(err: &SvsmReqError) -> bool {
+    matches!(
+        err,
+        SvsmReqError::FatalError(SvsmError::Ghcb(GhcbError::Busy))
+    )
+}
+
+/// Returns the certificate buffer size in bytes required by the hypervisor
+/// if `err` reports that the buffer passed to an extended guest request was
+/// too small.
+pub fn extract_required_cert_size(err: &SvsmReqError) -> Option<usize> {
+    match err {
+        SvsmReqError::FatalError(SvsmError::Ghcb(ghcb_err)) => ghcb_err.required_cert_size(),
+        _ => None,
+    }
+}
 
 /// Class of the `SNP_GUEST_REQUEST` command: Regular or Extended
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -152,16 +189,24 @@ impl SnpGuestRequestDriver {
     }
 
     /// Call the GHCB layer to send the encrypted SNP_GUEST_REQUEST message
-    /// to the PSP.
-    fn send(&mut self, req_class: SnpGuestRequestClass) -> Result<(), SvsmReqError> {
+    /// to the PSP, using the provided `ghcb`.
+    fn send(
+        &mut self,
+        ghcb: &mut GhcbGuard,
+        req_class: SnpGuestRequestClass,
+    ) -> Result<(), SvsmReqError> {
         self.response.clear();
 
         let req_page = VirtAddr::from(addr_of_mut!(*self.request));
         let resp_page = VirtAddr::from(addr_of_mut!(*self.response));
         let data_pages = VirtAddr::from(addr_of_mut!(*self.ext_data));
-        let mut ghcb = current_ghcb();
 
         if req_class == SnpGuestRequestClass::Extended {
+            // Extended guest requests have no feature bit of their own, they
+            // are part of the base SEV-SNP feature set of the GHCB protocol.
+            if !hv_features().has_snp() {
+                return Err(SvsmReqError::unsupported_call());
+            }
             let num_user_pages = (self.user_extdata_size >> PAGE_SHIFT) as u64;
             ghcb.guest_ext_request(req_page, resp_page, data_pages, num_user_pages)?;
         } else {
@@ -173,6 +218,27 @@ impl SnpGuestRequestDriver {
         Ok(())
     }
 
+    /// Resend the current `SNP_GUEST_REQUEST` message for as long as the
+    /// hypervisor reports it is busy, giving up once `deadline_tsc` has
+    /// passed.
+    fn resend_while_busy(
+        &mut self,
+        ghcb: &mut GhcbGuard,
+        req_class: SnpGuestRequestClass,
+        deadline_tsc: u64,
+    ) -> Result<(), SvsmReqError> {
+        loop {
+            match self.send(ghcb, req_class) {
+                Err(e) if is_hv_busy(&e) => {
+                    if rdtsc() > deadline_tsc {
+                        return Err(SvsmReqError::timeout());
+                    }
+                }
+                result => return result,
+            }
+        }
+    }
+
     // Encrypt the request message from encrypted memory
     fn encrypt_request(
         &mut self,
@@ -229,11 +295,14 @@ impl SnpGuestRequestDriver {
     ///
     /// # Arguments
     ///
+    /// * `ghcb`: GHCB used to communicate with the hypervisor
     /// * `req_class`: whether this is a regular or extended `SNP_GUEST_REQUEST` command
     /// * `msg_type`: type of the command stored in `buffer`, e.g. SNP_MSG_REPORT_REQ
     /// * `buffer`: buffer with the `SNP_GUEST_REQUEST` command to be sent.
     ///             The same buffer will also be used to store the response.
     /// * `command_len`: Size (in bytes) of the command stored in `buffer`
+    /// * `deadline_tsc`: TSC value after which a request the hypervisor keeps
+    ///                   reporting as busy is abandoned
     ///
     /// # Returns
     ///
@@ -243,10 +312,12 @@ impl SnpGuestRequestDriver {
     ///     * [`SvsmReqError`]
     fn send_request(
         &mut self,
+        ghcb: &mut GhcbGuard,
         req_class: SnpGuestRequestClass,
         msg_type: SnpGuestRequestMsgType,
         buffer: &mut [u8],
         command_len: usize,
+        deadline_tsc: u64,
     ) -> Result<usize, SvsmReqError> {
         if secrets_page().is_vmpck_clear(0) {
             return Err(SvsmReqError::invalid_request());
@@ -263,20 +334,18 @@ impl SnpGuestRequestDriver {
 
         self.encrypt_request(msg_type, msg_seqno, buffer, command_len)?;
 
-        if let Err(e) = self.send(req_class) {
-            if let SvsmReqError::FatalError(SvsmError::Ghcb(GhcbError::VmgexitError(_rbx, info2))) =
-                e
-            {
+        if let Err(e) = self.send(ghcb, req_class) {
+            if let SvsmReqError::FatalError(SvsmError::Ghcb(ghcb_err)) = e {
                 // For some reason the hypervisor did not forward the request to the PSP.
                 //
                 // Because the message sequence number is used as part of the AES-GCM IV, it is important that the
                 // guest retry the request before allowing another request to be performed so that the IV cannot be
                 // reused on a new message payload.
-                match info2 & 0xffff_ffff_0000_0000u64 {
+                match ghcb_err {
                     // The certificate buffer provided is too small.
-                    SNP_GUEST_REQ_INVALID_LEN => {
+                    GhcbError::CertsBufferTooSmall { .. } => {
                         if req_class == SnpGuestRequestClass::Extended {
-                            if let Err(e1) = self.send(SnpGuestRequestClass::Regular) {
+                            if let Err(e1) = self.send(ghcb, SnpGuestRequestClass::Regular) {
                                 log::error!(
                                     "SNP_GUEST_REQ_INVALID_LEN. Aborting, request resend failed"
                                 );
@@ -292,8 +361,8 @@ impl SnpGuestRequestDriver {
                         }
                     }
                     // The hypervisor is busy.
-                    SNP_GUEST_REQ_ERR_BUSY => {
-                        if let Err(e2) = self.send(req_class) {
+                    GhcbError::Busy => {
+                        if let Err(e2) = self.resend_while_busy(ghcb, req_class, deadline_tsc) {
                             log::error!("SNP_GUEST_REQ_ERR_BUSY. Aborting, request resend failed");
                             secrets_page_mut().clear_vmpck(0);
                             return Err(e2);
@@ -303,7 +372,7 @@ impl SnpGuestRequestDriver {
                     // Failed for unknown reason. Status codes can be found in
                     // the AMD SEV-SNP spec or in the linux kernel include/uapi/linux/psp-sev.h
                     _ => {
-                        log::error!("SNP_GUEST_REQUEST failed, unknown error code={}\n", info2);
+                        log::error!("SNP_GUEST_REQUEST {} failed: {:?}", msg_type, ghcb_err);
                         secrets_page_mut().clear_vmpck(0);
                         return Err(e);
                     }
@@ -320,28 +389,41 @@ impl SnpGuestRequestDriver {
     /// Send the provided regular `SNP_GUEST_REQUEST` command to the PSP
     pub fn send_regular_guest_request(
         &mut self,
+        ghcb: &mut GhcbGuard,
         msg_type: SnpGuestRequestMsgType,
         buffer: &mut [u8],
         command_len: usize,
+        deadline_tsc: u64,
     ) -> Result<usize, SvsmReqError> {
-        self.send_request(SnpGuestRequestClass::Regular, msg_type, buffer, command_len)
+        self.send_request(
+            ghcb,
+            SnpGuestRequestClass::Regular,
+            msg_type,
+            buffer,
+            command_len,
+            deadline_tsc,
+        )
     }
 
     /// Send the provided extended `SNP_GUEST_REQUEST` command to the PSP
     pub fn send_extended_guest_request(
         &mut self,
+        ghcb: &mut GhcbGuard,
         msg_type: SnpGuestRequestMsgType,
         buffer: &mut [u8],
         command_len: usize,
         certs: &mut [u8],
+        deadline_tsc: u64,
     ) -> Result<usize, SvsmReqError> {
         self.set_user_extdata_size(certs.len())?;
 
         let outbuf_len: usize = self.send_request(
+            ghcb,
             SnpGuestRequestClass::Extended,
             msg_type,
             buffer,
             command_len,
+            deadline_tsc,
         )?;
 
         // The SEV-SNP certificates can be used to verify the attestation report. At this point, a zeroed
@@ -357,41 +439,136 @@ impl SnpGuestRequestDriver {
     }
 }
 
-/// Initialize the global `SnpGuestRequestDriver`
-///
-/// # Panics
+/// Initialize the global `SnpGuestRequestDriver`. Does nothing if it has
+/// already been initialized.
 ///
-/// This function panics if we fail to initialize any of the `SnpGuestRequestDriver` fields.
-pub fn guest_request_driver_init() {
+/// Fails if any of the `SnpGuestRequestDriver` fields cannot be initialized.
+pub fn guest_request_driver_init() -> Result<(), SvsmReqError> {
     let cell = GREQ_DRIVER.lock();
-    let _ = cell.get_or_init(|| {
-        SnpGuestRequestDriver::new().expect("SnpGuestRequestDriver failed to initialize")
-    });
+    if cell.get().is_none() {
+        let _ = cell.set(SnpGuestRequestDriver::new()?);
+    }
+    Ok(())
 }
 
-/// Send the provided regular `SNP_GUEST_REQUEST` command to the PSP.
+/// Send the provided regular `SNP_GUEST_REQUEST` command to the PSP, using
+/// the default timeout.
 /// Further details can be found in the `SnpGuestRequestDriver.send_request()` documentation.
 pub fn send_regular_guest_request(
+    ghcb: &mut GhcbGuard,
     msg_type: SnpGuestRequestMsgType,
     buffer: &mut [u8],
     request_len: usize,
+) -> Result<usize, SvsmReqError> {
+    send_guest_request_with_timeout(
+        ghcb,
+        msg_type,
+        buffer,
+        request_len,
+        guest_request_deadline(),
+    )
+}
+
+/// Send the provided regular `SNP_GUEST_REQUEST` command to the PSP,
+/// giving up with [`SvsmReqError::timeout()`] if the hypervisor still
+/// reports busy at `deadline_tsc`.
+/// Further details can be found in the `SnpGuestRequestDriver.send_request()` documentation.
+pub fn send_guest_request_with_timeout(
+    ghcb: &mut GhcbGuard,
+    msg_type: SnpGuestRequestMsgType,
+    buffer: &mut [u8],
+    request_len: usize,
+    deadline_tsc: u64,
 ) -> Result<usize, SvsmReqError> {
     let mut cell = GREQ_DRIVER.lock();
     let driver: &mut SnpGuestRequestDriver =
         cell.get_mut().ok_or_else(SvsmReqError::invalid_request)?;
-    driver.send_regular_guest_request(msg_type, buffer, request_len)
+    driver.send_regular_guest_request(ghcb, msg_type, buffer, request_len, deadline_tsc)
 }
 
-/// Send the provided extended `SNP_GUEST_REQUEST` command to the PSP
+/// Send the provided extended `SNP_GUEST_REQUEST` command to the PSP, using
+/// the default timeout.
 /// Further details can be found in the `SnpGuestRequestDriver.send_request()` documentation.
 pub fn send_extended_guest_request(
+    ghcb: &mut GhcbGuard,
     msg_type: SnpGuestRequestMsgType,
     buffer: &mut [u8],
     request_len: usize,
     certs: &mut [u8],
+) -> Result<usize, SvsmReqError> {
+    send_extended_guest_request_with_timeout(
+        ghcb,
+        msg_type,
+        buffer,
+        request_len,
+        certs,
+        guest_request_deadline(),
+    )
+}
+
+/// Send the provided extended `SNP_GUEST_REQUEST` command to the PSP,
+/// giving up with [`SvsmReqError::timeout()`] if the hypervisor still
+/// reports busy at `deadline_tsc`.
+/// Further details can be found in the `SnpGuestRequestDriver.send_request()` documentation.
+pub fn send_extended_guest_request_with_timeout(
+    ghcb: &mut GhcbGuard,
+    msg_type: SnpGuestRequestMsgType,
+    buffer: &mut [u8],
+    request_len: usize,
+    certs: &mut [u8],
+    deadline_tsc: u64,
 ) -> Result<usize, SvsmReqError> {
     let mut cell = GREQ_DRIVER.lock();
     let driver: &mut SnpGuestRequestDriver =
         cell.get_mut().ok_or_else(SvsmReqError::invalid_request)?;
-    driver.send_extended_guest_request(msg_type, buffer, request_len, certs)
+    driver.send_extended_guest_request(ghcb, msg_type, buffer, request_len, certs, deadline_tsc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_hv_busy() {
+        let busy = SvsmReqError::FatalError(SvsmError::Ghcb(GhcbError::Busy));
+        let invalid_len =
+            SvsmReqError::FatalError(SvsmError::Ghcb(GhcbError::CertsBufferTooSmall {
+                required: 1,
+            }));
+
+        assert!(is_hv_busy(&busy));
+        assert!(!is_hv_busy(&invalid_len));
+        assert!(!is_hv_busy(&SvsmReqError::invalid_request()));
+        assert!(!is_hv_busy(&SvsmReqError::timeout()));
+    }
+
+    #[test]
+    fn test_timeout_is_not_retriable() {
+        // The VMPCK0 is cleared when a busy request times out, so the guest
+        // must not be told to retry.
+        assert!(!SvsmReqError::timeout().is_retriable());
+        assert!(matches!(
+            SvsmReqError::timeout(),
+            SvsmReqError::FatalError(SvsmError::Timeout)
+        ));
+    }
+
+    #[test]
+    fn test_extract_required_cert_size() {
+        let invalid_len =
+            SvsmReqError::FatalError(SvsmError::Ghcb(GhcbError::CertsBufferTooSmall {
+                required: 2,
+            }));
+        let busy = SvsmReqError::FatalError(SvsmError::Ghcb(GhcbError::Busy));
+
+        assert_eq!(
+            extract_required_cert_size(&invalid_len),
+            Some(2 * crate::types::PAGE_SIZE)
+        );
+        assert_eq!(extract_required_cert_size(&busy), None);
+        assert_eq!(
+            extract_required_cert_size(&SvsmReqError::invalid_request()),
+            None
+        );
+    }
 }
@@ -10,3 +10,5 @@ pub mod driver;
 pub mod msg;
 pub mod pld_report;
 pub mod services;
+#[cfg(test)]
+pub mod testing;
@@ -13,6 +13,7 @@ use alloc::{
     boxed::Box,
 };
 use core::{
+    fmt,
     mem::size_of,
     ptr::{addr_of, addr_of_mut},
     slice::{from_raw_parts, from_raw_parts_mut, from_ref},
@@ -46,20 +47,89 @@ pub enum SnpGuestRequestAead {
 #[repr(u8)]
 pub enum SnpGuestRequestMsgType {
     Invalid = 0,
+    CpuidRequest = 1,
+    CpuidResponse = 2,
+    KeyRequest = 3,
+    KeyResponse = 4,
     ReportRequest = 5,
     ReportResponse = 6,
+    ExportRequest = 7,
+    ExportResponse = 8,
+    ImportRequest = 9,
+    ImportResponse = 10,
+    AbsorbRequest = 11,
+    AbsorbResponse = 12,
+    VmrkRequest = 13,
+    VmrkResponse = 14,
+    AbsorbNomaRequest = 15,
+    AbsorbNomaResponse = 16,
+    TscInfoRequest = 17,
+    TscInfoResponse = 18,
+}
+
+impl SnpGuestRequestMsgType {
+    const ALL: [Self; 19] = [
+        Self::Invalid,
+        Self::CpuidRequest,
+        Self::CpuidResponse,
+        Self::KeyRequest,
+        Self::KeyResponse,
+        Self::ReportRequest,
+        Self::ReportResponse,
+        Self::ExportRequest,
+        Self::ExportResponse,
+        Self::ImportRequest,
+        Self::ImportResponse,
+        Self::AbsorbRequest,
+        Self::AbsorbResponse,
+        Self::VmrkRequest,
+        Self::VmrkResponse,
+        Self::AbsorbNomaRequest,
+        Self::AbsorbNomaResponse,
+        Self::TscInfoRequest,
+        Self::TscInfoResponse,
+    ];
+
+    /// Name of the message type as used in the SEV-SNP specification
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Invalid => "MSG_TYPE_INVALID",
+            Self::CpuidRequest => "MSG_CPUID_REQ",
+            Self::CpuidResponse => "MSG_CPUID_RSP",
+            Self::KeyRequest => "MSG_KEY_REQ",
+            Self::KeyResponse => "MSG_KEY_RSP",
+            Self::ReportRequest => "MSG_REPORT_REQ",
+            Self::ReportResponse => "MSG_REPORT_RSP",
+            Self::ExportRequest => "MSG_EXPORT_REQ",
+            Self::ExportResponse => "MSG_EXPORT_RSP",
+            Self::ImportRequest => "MSG_IMPORT_REQ",
+            Self::ImportResponse => "MSG_IMPORT_RSP",
+            Self::AbsorbRequest => "MSG_ABSORB_REQ",
+            Self::AbsorbResponse => "MSG_ABSORB_RSP",
+            Self::VmrkRequest => "MSG_VMRK_REQ",
+            Self::VmrkResponse => "MSG_VMRK_RSP",
+            Self::AbsorbNomaRequest => "MSG_ABSORB_NOMA_REQ",
+            Self::AbsorbNomaResponse => "MSG_ABSORB_NOMA_RSP",
+            Self::TscInfoRequest => "MSG_TSC_INFO_REQ",
+            Self::TscInfoResponse => "MSG_TSC_INFO_RSP",
+        }
+    }
 }
 
 impl TryFrom<u8> for SnpGuestRequestMsgType {
     type Error = SvsmReqError;
 
     fn try_from(v: u8) -> Result<Self, Self::Error> {
-        match v {
-            x if x == Self::Invalid as u8 => Ok(Self::Invalid),
-            x if x == Self::ReportRequest as u8 => Ok(Self::ReportRequest),
-            x if x == Self::ReportResponse as u8 => Ok(Self::ReportResponse),
-            _ => Err(SvsmReqError::invalid_parameter()),
-        }
+        Self::ALL
+            .get(usize::from(v))
+            .copied()
+            .ok_or_else(SvsmReqError::invalid_parameter)
+    }
+}
+
+impl fmt::Display for SnpGuestRequestMsgType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
     }
 }
 
@@ -483,6 +553,22 @@ mod tests {
     use crate::mm::alloc::{TestRootMem, DEFAULT_TEST_MEMORY_SIZE};
     use core::mem::offset_of;
 
+    #[test]
+    fn test_snp_guest_request_msg_type() {
+        for (i, msg_type) in SnpGuestRequestMsgType::ALL.iter().enumerate() {
+            assert_eq!(*msg_type as usize, i);
+            assert_eq!(
+                SnpGuestRequestMsgType::try_from(i as u8).unwrap(),
+                *msg_type
+            );
+        }
+        assert!(SnpGuestRequestMsgType::try_from(19).is_err());
+        assert_eq!(
+            SnpGuestRequestMsgType::ReportRequest.name(),
+            "MSG_REPORT_REQ"
+        );
+    }
+
     #[test]
     fn test_snp_guest_request_hdr_offsets() {
         assert_eq!(offset_of!(SnpGuestRequestMsgHdr, authtag), 0);
@@ -6,10 +6,14 @@
 
 //! `SNP_GUEST_REQUEST` command to request an attestation report.
 
+use core::cmp::Ordering;
+use core::fmt;
 use core::mem::size_of;
 
 use crate::protocols::errors::SvsmReqError;
 
+include!(concat!(env!("OUT_DIR"), "/min_tcb.rs"));
+
 /// Size of the `SnpReportRequest.user_data`
 pub const USER_DATA_SIZE: usize = 64;
 
@@ -33,9 +37,36 @@ pub struct SnpReportRequest {
     rsvd: [u8; 24],
 }
 
+/// Size of the [`SnpReportRequest`] payload
+pub const REPORT_REQUEST_SIZE: usize = size_of::<SnpReportRequest>();
+/// Size of the [`SnpReportResponse`] payload
+pub const REPORT_RESPONSE_SIZE: usize = size_of::<SnpReportResponse>();
+
+const _: () = assert!(REPORT_REQUEST_SIZE == 0x60);
+const _: () = assert!(REPORT_RESPONSE_SIZE == 0x4c0);
+
 impl SnpReportRequest {
+    /// Create a request for a report signed with the default key and
+    /// carrying the given `user_data` and `vmpl`.
+    pub fn new(user_data: &[u8; USER_DATA_SIZE], vmpl: u8) -> Self {
+        Self {
+            user_data: *user_data,
+            vmpl: vmpl.into(),
+            flags: 0,
+            rsvd: [0; 24],
+        }
+    }
+
+    /// Serialize the request into its raw payload format.
+    pub fn into_buffer(self) -> [u8; REPORT_REQUEST_SIZE] {
+        // SAFETY: SnpReportRequest is repr(packed) and comprised entirely of
+        // integer types, so it has no padding and its size matches the
+        // buffer.
+        unsafe { core::mem::transmute::<Self, [u8; REPORT_REQUEST_SIZE]>(self) }
+    }
+
     /// Take a slice and return a reference for Self
-    pub fn try_from_as_ref(buffer: &[u8]) -> Result<&Self, SvsmReqError> {
+    pub fn from_buffer(buffer: &[u8]) -> Result<&Self, SvsmReqError> {
         let buffer = buffer
             .get(..size_of::<Self>())
             .ok_or_else(SvsmReqError::invalid_parameter)?;
@@ -86,7 +117,16 @@ pub enum SnpReportResponseStatus {
 }
 
 impl SnpReportResponse {
-    pub fn try_from_as_ref(buffer: &[u8]) -> Result<&Self, SvsmReqError> {
+    /// Serialize the response into its raw payload format.
+    pub fn into_buffer(self) -> [u8; REPORT_RESPONSE_SIZE] {
+        // SAFETY: SnpReportResponse is repr(packed) and comprised entirely of
+        // integer types, so it has no padding and its size matches the
+        // buffer.
+        unsafe { core::mem::transmute::<Self, [u8; REPORT_RESPONSE_SIZE]>(self) }
+    }
+
+    /// Take a slice and return a reference for Self
+    pub fn from_buffer(buffer: &[u8]) -> Result<&Self, SvsmReqError> {
         let buffer = buffer
             .get(..size_of::<Self>())
             .ok_or_else(SvsmReqError::invalid_parameter)?;
@@ -99,6 +139,87 @@ impl SnpReportResponse {
         Ok(response)
     }
 
+    /// The measurement calculated at launch.
+    ///
+    /// ```rust
+    /// # use svsm::greq::pld_report::{SnpReportResponse, REPORT_RESPONSE_SIZE};
+    /// let mut buffer = [0u8; REPORT_RESPONSE_SIZE];
+    /// buffer[0xb0] = 0xaa;
+    /// let response = SnpReportResponse::from_buffer(&buffer).unwrap();
+    /// assert_eq!(response.measurement()[0], 0xaa);
+    /// ```
+    pub fn measurement(&self) -> &[u8; 48] {
+        &self.report.measurement
+    }
+
+    /// The data provided by the hypervisor at launch.
+    ///
+    /// ```rust
+    /// # use svsm::greq::pld_report::{SnpReportResponse, REPORT_RESPONSE_SIZE};
+    /// let mut buffer = [0u8; REPORT_RESPONSE_SIZE];
+    /// buffer[0xe0] = 0xaa;
+    /// let response = SnpReportResponse::from_buffer(&buffer).unwrap();
+    /// assert_eq!(response.host_data()[0], 0xaa);
+    /// ```
+    pub fn host_data(&self) -> &[u8; 32] {
+        &self.report.host_data
+    }
+
+    /// The guest-provided data included in the report.
+    ///
+    /// ```rust
+    /// # use svsm::greq::pld_report::{SnpReportResponse, REPORT_RESPONSE_SIZE};
+    /// let mut buffer = [0u8; REPORT_RESPONSE_SIZE];
+    /// buffer[0x70] = 0xaa;
+    /// let response = SnpReportResponse::from_buffer(&buffer).unwrap();
+    /// assert_eq!(response.report_data()[0], 0xaa);
+    /// ```
+    pub fn report_data(&self) -> &[u8; 64] {
+        &self.report.report_data
+    }
+
+    /// The guest SVN.
+    ///
+    /// ```rust
+    /// # use svsm::greq::pld_report::{SnpReportResponse, REPORT_RESPONSE_SIZE};
+    /// let mut buffer = [0u8; REPORT_RESPONSE_SIZE];
+    /// buffer[0x24] = 0x02;
+    /// let response = SnpReportResponse::from_buffer(&buffer).unwrap();
+    /// assert_eq!(response.guest_svn(), 2);
+    /// ```
+    pub fn guest_svn(&self) -> u32 {
+        self.report.guest_svn
+    }
+
+    /// The TCB version used to derive the VCEK that signed the report.
+    ///
+    /// ```rust
+    /// # use svsm::greq::pld_report::{SnpReportResponse, REPORT_RESPONSE_SIZE};
+    /// let mut buffer = [0u8; REPORT_RESPONSE_SIZE];
+    /// buffer[0x1a0] = 3;
+    /// buffer[0x1a7] = 0xd1;
+    /// let response = SnpReportResponse::from_buffer(&buffer).unwrap();
+    /// let tcb = response.tcb_version();
+    /// assert_eq!(tcb.boot_loader, 3);
+    /// assert_eq!(tcb.microcode, 0xd1);
+    /// ```
+    pub fn tcb_version(&self) -> TcbVersion {
+        self.report.reported_tcb
+    }
+
+    /// The VMPL the report was requested for.
+    ///
+    /// ```rust
+    /// # use svsm::greq::pld_report::{SnpReportResponse, REPORT_RESPONSE_SIZE};
+    /// let mut buffer = [0u8; REPORT_RESPONSE_SIZE];
+    /// buffer[0x50] = 1;
+    /// let response = SnpReportResponse::from_buffer(&buffer).unwrap();
+    /// assert_eq!(response.vmpl(), 1);
+    /// ```
+    pub fn vmpl(&self) -> u8 {
+        self.report.vmpl as u8
+    }
+
     /// Validate the [SnpReportResponse] fields
     pub fn validate(&self) -> Result<(), SvsmReqError> {
         if self.status != SnpReportResponseStatus::Success as u32 {
@@ -118,9 +239,73 @@ impl SnpReportResponse {
 /// (AMD SEV-SNP spec. table 3)
 #[repr(C, packed)]
 #[derive(Clone, Copy, Debug)]
-struct TcbVersion {
-    /// Version of the Microcode, SNP firmware, PSP and boot loader
-    raw: u64,
+pub struct TcbVersion {
+    /// SVN of the PSP boot loader
+    pub boot_loader: u8,
+    /// SVN of the PSP operating system
+    pub tee: u8,
+    /// Reserved
+    _reserved: [u8; 4],
+    /// SVN of the SNP firmware
+    pub snp: u8,
+    /// Lowest current patch level of all the cores
+    pub microcode: u8,
+}
+
+impl TcbVersion {
+    pub const fn new(boot_loader: u8, tee: u8, snp: u8, microcode: u8) -> Self {
+        Self {
+            boot_loader,
+            tee,
+            _reserved: [0; 4],
+            snp,
+            microcode,
+        }
+    }
+}
+
+/// Versions are compared on their SVNs only, the reserved bytes are
+/// ignored as in the [`PartialOrd`] implementation.
+impl PartialEq for TcbVersion {
+    fn eq(&self, other: &Self) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+impl Eq for TcbVersion {}
+
+/// TCB versions are only partially ordered: one version is below another
+/// if none of its components is newer. Two versions where each has a newer
+/// component than the other are not comparable.
+impl PartialOrd for TcbVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        let pairs = [
+            (self.boot_loader, other.boot_loader),
+            (self.tee, other.tee),
+            (self.snp, other.snp),
+            (self.microcode, other.microcode),
+        ];
+        let mut order = Ordering::Equal;
+        for (a, b) in pairs {
+            match (order, a.cmp(&b)) {
+                (_, Ordering::Equal) => {}
+                (Ordering::Equal, o) => order = o,
+                (o, p) if o != p => return None,
+                _ => {}
+            }
+        }
+        Some(order)
+    }
+}
+
+impl fmt::Display for TcbVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "boot_loader={} tee={} snp={} microcode={}",
+            self.boot_loader, self.tee, self.snp, self.microcode
+        )
+    }
 }
 
 /// Format for an ECDSA P-384 with SHA-384 signature (AMD SEV-SNP spec. table 115)
@@ -190,7 +375,51 @@ pub struct AttestationReport {
     signature: Signature,
 }
 
-const _: () = assert!(size_of::<AttestationReport>() <= u32::MAX as usize);
+const _: () = assert!(size_of::<AttestationReport>() == 0x4a0);
+
+/// Minimum TCB version accepted by [`enforce_minimum_tcb()`]. Set at build
+/// time through the `SVSM_MIN_TCB_VERSION` environment variable, given as
+/// `boot_loader:tee:snp:microcode`. Defaults to all zeroes.
+pub const MIN_TCB_VERSION: TcbVersion = TcbVersion::new(
+    MIN_TCB_BOOT_LOADER,
+    MIN_TCB_TEE,
+    MIN_TCB_SNP,
+    MIN_TCB_MICROCODE,
+);
+
+/// Returns whether every component of the reported TCB version is at least
+/// the corresponding component of `min`.
+pub fn tcb_version_meets_minimum(response: &SnpReportResponse, min: TcbVersion) -> bool {
+    response.tcb_version() >= min
+}
+
+/// Rejects a report whose TCB version is below [`MIN_TCB_VERSION`].
+pub fn enforce_minimum_tcb(response: &SnpReportResponse) -> Result<(), SvsmReqError> {
+    if tcb_version_meets_minimum(response, MIN_TCB_VERSION) {
+        Ok(())
+    } else {
+        log::error!(
+            "Reported TCB version ({}) is below the minimum ({})",
+            response.tcb_version(),
+            MIN_TCB_VERSION
+        );
+        Err(SvsmReqError::invalid_request())
+    }
+}
+
+/// Panics if the TCB version of a report requested by the SVSM itself is
+/// below [`MIN_TCB_VERSION`]. Attestation of the SVSM cannot be trusted on
+/// such a platform, so this is a fatal condition rather than a request
+/// error.
+pub fn assert_minimum_tcb(response: &SnpReportResponse) {
+    if !tcb_version_meets_minimum(response, MIN_TCB_VERSION) {
+        panic!(
+            "Insecure TCB: version ({}) < minimum ({})",
+            response.tcb_version(),
+            MIN_TCB_VERSION
+        );
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -205,6 +434,23 @@ mod tests {
         assert_eq!(offset_of!(SnpReportRequest, rsvd), 0x48);
     }
 
+    #[test]
+    fn test_snp_report_request_buffer() {
+        let user_data = [0xab; USER_DATA_SIZE];
+        let buffer = SnpReportRequest::new(&user_data, 0).into_buffer();
+        assert_eq!(buffer[..USER_DATA_SIZE], user_data);
+        assert!(buffer[USER_DATA_SIZE..].iter().all(|b| *b == 0));
+
+        let request = SnpReportRequest::from_buffer(&buffer).unwrap();
+        assert!(request.is_vmpl0());
+        assert!(SnpReportRequest::from_buffer(&buffer[1..]).is_err());
+
+        let mut buffer = SnpReportRequest::new(&user_data, 1).into_buffer();
+        assert!(!SnpReportRequest::from_buffer(&buffer).unwrap().is_vmpl0());
+        buffer[REPORT_REQUEST_SIZE - 1] = 1;
+        assert!(SnpReportRequest::from_buffer(&buffer).is_err());
+    }
+
     #[test]
     fn test_snp_report_response_offsets() {
         assert_eq!(offset_of!(SnpReportResponse, status), 0x0);
@@ -213,6 +459,36 @@ mod tests {
         assert_eq!(offset_of!(SnpReportResponse, report), 0x20);
     }
 
+    #[test]
+    fn test_tcb_version_offsets() {
+        assert_eq!(size_of::<TcbVersion>(), 8);
+        assert_eq!(offset_of!(TcbVersion, boot_loader), 0x0);
+        assert_eq!(offset_of!(TcbVersion, tee), 0x1);
+        assert_eq!(offset_of!(TcbVersion, snp), 0x6);
+        assert_eq!(offset_of!(TcbVersion, microcode), 0x7);
+    }
+
+    #[test]
+    fn test_tcb_version_meets_minimum() {
+        let mut buffer = [0u8; REPORT_RESPONSE_SIZE];
+        // boot_loader=3 tee=0 snp=8 microcode=0xd1
+        buffer[0x1a0] = 3;
+        buffer[0x1a6] = 8;
+        buffer[0x1a7] = 0xd1;
+        let response = SnpReportResponse::from_buffer(&buffer).unwrap();
+
+        for (min, expected) in [
+            (TcbVersion::new(0, 0, 0, 0), true),
+            (TcbVersion::new(3, 0, 8, 0xd1), true),
+            (TcbVersion::new(4, 0, 0, 0), false),
+            (TcbVersion::new(0, 1, 0, 0), false),
+            (TcbVersion::new(0, 0, 9, 0), false),
+            (TcbVersion::new(0, 0, 0, 0xd2), false),
+        ] {
+            assert_eq!(tcb_version_meets_minimum(response, min), expected);
+        }
+    }
+
     #[test]
     fn test_ecdsa_p384_sha384_signature_offsets() {
         assert_eq!(offset_of!(Signature, r), 0x0);
@@ -246,4 +522,24 @@ mod tests {
         assert_eq!(offset_of!(AttestationReport, reserved2), 0x1e0);
         assert_eq!(offset_of!(AttestationReport, signature), 0x2a0);
     }
+
+    #[test]
+    fn test_tcb_version_partial_ord() {
+        let base = TcbVersion::new(3, 0, 8, 0xd1);
+        assert_eq!(base.partial_cmp(&base), Some(Ordering::Equal));
+        assert!(TcbVersion::new(3, 0, 8, 0xd0) < base);
+        assert!(TcbVersion::new(4, 0, 8, 0xd1) > base);
+        // Newer SNP firmware does not make up for older microcode.
+        let mixed = TcbVersion::new(3, 0, 9, 0xd0);
+        assert_eq!(mixed.partial_cmp(&base), None);
+        assert!(!(mixed >= base));
+        assert_ne!(mixed, base);
+
+        let reserved = TcbVersion {
+            _reserved: [1; 4],
+            ..base
+        };
+        assert_eq!(reserved, base);
+        assert_eq!(reserved.partial_cmp(&base), Some(Ordering::Equal));
+    }
 }
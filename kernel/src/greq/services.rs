@@ -6,49 +6,106 @@
 
 //! API to send `SNP_GUEST_REQUEST` commands to the PSP
 
+extern crate alloc;
+
 use crate::{
+    cpu::ghcb::this_cpu_ghcb,
+    error::SvsmError,
     greq::{
-        driver::{send_extended_guest_request, send_regular_guest_request},
-        msg::SnpGuestRequestMsgType,
-        pld_report::{SnpReportRequest, SnpReportResponse},
+        driver::{
+            extract_required_cert_size, guest_request_deadline,
+            send_extended_guest_request_with_timeout, send_guest_request_with_timeout,
+        },
+        msg::{SnpGuestRequestMsgType, SNP_GUEST_REQ_MAX_DATA_SIZE},
+        pld_report::{
+            assert_minimum_tcb, enforce_minimum_tcb, SnpReportRequest, SnpReportResponse,
+            TcbVersion, MIN_TCB_VERSION, REPORT_REQUEST_SIZE, REPORT_RESPONSE_SIZE, USER_DATA_SIZE,
+        },
     },
     protocols::errors::SvsmReqError,
+    types::PAGE_SIZE,
 };
-use core::mem::size_of;
-
-const REPORT_REQUEST_SIZE: usize = size_of::<SnpReportRequest>();
-const REPORT_RESPONSE_SIZE: usize = size_of::<SnpReportResponse>();
+use alloc::vec;
+use alloc::vec::Vec;
 
-fn get_report(buffer: &mut [u8], certs: Option<&mut [u8]>) -> Result<usize, SvsmReqError> {
-    let request: &SnpReportRequest = SnpReportRequest::try_from_as_ref(buffer)?;
-    // Non-VMPL0 attestation reports can be requested by the guest kernel
-    // directly to the PSP.
-    if !request.is_vmpl0() {
-        return Err(SvsmReqError::invalid_parameter());
+fn send_report_request(buffer: &mut [u8], certs: Option<&mut [u8]>) -> Result<usize, SvsmReqError> {
+    #[cfg(test)]
+    if let Some(result) = crate::greq::testing::mock_send(
+        SnpGuestRequestMsgType::ReportRequest,
+        buffer,
+        REPORT_REQUEST_SIZE,
+    ) {
+        return result;
     }
-    let response_len = if certs.is_none() {
-        send_regular_guest_request(
+
+    let mut ghcb = this_cpu_ghcb(0)?;
+    let deadline = guest_request_deadline();
+    if let Some(certs) = certs {
+        send_extended_guest_request_with_timeout(
+            &mut ghcb,
             SnpGuestRequestMsgType::ReportRequest,
             buffer,
             REPORT_REQUEST_SIZE,
-        )?
+            certs,
+            deadline,
+        )
     } else {
-        send_extended_guest_request(
+        send_guest_request_with_timeout(
+            &mut ghcb,
             SnpGuestRequestMsgType::ReportRequest,
             buffer,
             REPORT_REQUEST_SIZE,
-            certs.unwrap(),
-        )?
-    };
+            deadline,
+        )
+    }
+}
+
+fn request_report(buffer: &mut [u8], certs: Option<&mut [u8]>) -> Result<usize, SvsmReqError> {
+    let request: &SnpReportRequest = SnpReportRequest::from_buffer(buffer)?;
+    // Non-VMPL0 attestation reports can be requested by the guest kernel
+    // directly to the PSP.
+    if !request.is_vmpl0() {
+        return Err(SvsmReqError::invalid_parameter());
+    }
+    // VMPL0 reports are requested on behalf of the SVSM itself.
+    let response_len = send_report_request(buffer, certs)?;
     if REPORT_RESPONSE_SIZE > response_len {
         return Err(SvsmReqError::invalid_request());
     }
-    let response: &SnpReportResponse = SnpReportResponse::try_from_as_ref(buffer)?;
+    let response: &SnpReportResponse = SnpReportResponse::from_buffer(buffer)?;
     response.validate()?;
 
     Ok(response_len)
 }
 
+fn get_report(buffer: &mut [u8], certs: Option<&mut [u8]>) -> Result<usize, SvsmReqError> {
+    let response_len = request_report(buffer, certs)?;
+    enforce_minimum_tcb(SnpReportResponse::from_buffer(buffer)?)?;
+    Ok(response_len)
+}
+
+/// Checks at boot that the platform TCB version meets
+/// [`MIN_TCB_VERSION`] by requesting a VMPL0 attestation report. Nothing
+/// is requested if no minimum has been configured.
+///
+/// # Panics
+///
+/// Panics if the report cannot be obtained or its TCB version is too old.
+pub fn check_boot_tcb() {
+    if MIN_TCB_VERSION == TcbVersion::new(0, 0, 0, 0) {
+        return;
+    }
+
+    let mut buffer = vec![0u8; REPORT_RESPONSE_SIZE];
+    let request = SnpReportRequest::new(&[0; USER_DATA_SIZE], 0).into_buffer();
+    buffer[..REPORT_REQUEST_SIZE].copy_from_slice(&request);
+
+    request_report(&mut buffer, None).expect("Failed to request attestation report for TCB check");
+    let response =
+        SnpReportResponse::from_buffer(&buffer).expect("Invalid attestation report for TCB check");
+    assert_minimum_tcb(response);
+}
+
 /// Request a regular VMPL0 attestation report to the PSP.
 ///
 /// Use the `SNP_GUEST_REQUEST` driver to send the provided `MSG_REPORT_REQ` command to
@@ -97,10 +154,131 @@ pub fn get_regular_report(buffer: &mut [u8]) -> Result<usize, SvsmReqError> {
 ///                the [`MSG_REPORT_RESP`](SnpReportResponse) size.
 /// * Error
 ///     * [`SvsmReqError`]
-///     * `SvsmReqError::FatalError(SvsmError::Ghcb(GhcbError::VmgexitError(certs_buffer_size, psp_rc)))`:
+///     * `SvsmReqError::FatalError(SvsmError::Ghcb(GhcbError::CertsBufferTooSmall { required }))`:
 ///         * `certs` is not large enough to hold the certificates.
-///             * `certs_buffer_size`: number of bytes required.
-///             * `psp_rc`: PSP return code
+///             * `required`: number of pages required.
 pub fn get_extended_report(buffer: &mut [u8], certs: &mut [u8]) -> Result<usize, SvsmReqError> {
     get_report(buffer, Some(certs))
 }
+
+/// Request an extended VMPL0 attestation report to the PSP, sizing the
+/// certificate buffer as needed.
+///
+/// Like [`get_extended_report()`], but starts with a one-page certificate
+/// buffer and retries once with the size reported by the hypervisor if that
+/// is too small. The size reported by the hypervisor is not trusted: sizes
+/// above [`SNP_GUEST_REQ_MAX_DATA_SIZE`] are rejected without retrying.
+///
+/// # Returns
+///
+/// * Success
+///     * `(usize, Vec<u8>)`: Number of bytes written to `buffer` and the
+///        certificates received from the hypervisor.
+/// * Error
+///     * [`SvsmReqError`]
+///     * `SvsmReqError::FatalError(SvsmError::Ghcb(GhcbError::CertsBufferTooSmall { required }))`:
+///         * The hypervisor requires a certificate buffer larger than
+///           [`SNP_GUEST_REQ_MAX_DATA_SIZE`].
+///     * `SvsmReqError::FatalError(SvsmError::Mem)`:
+///         * The certificate buffer could not be allocated.
+pub fn get_extended_report_auto(buffer: &mut [u8]) -> Result<(usize, Vec<u8>), SvsmReqError> {
+    let mut certs = vec![0u8; PAGE_SIZE];
+    match get_extended_report(buffer, &mut certs) {
+        Ok(len) => return Ok((len, certs)),
+        Err(e) => {
+            let Some(required) = extract_required_cert_size(&e) else {
+                return Err(e);
+            };
+            if required > SNP_GUEST_REQ_MAX_DATA_SIZE {
+                return Err(e);
+            }
+            certs
+                .try_reserve(required.saturating_sub(certs.len()))
+                .map_err(|_| SvsmError::Mem)?;
+            certs.resize(required, 0);
+        }
+    }
+    let len = get_extended_report(buffer, &mut certs)?;
+    Ok((len, certs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::greq::testing::{set_mock_transport, MockGhcb};
+    use crate::protocols::errors::SvsmResultCode;
+    use crate::sev::ghcb::GhcbError;
+
+    const REPORT_SIZE: u32 = 0x4a0;
+
+    fn request_buffer(vmpl: u8) -> (SnpReportRequest, Vec<u8>) {
+        let request = SnpReportRequest::new(&[0x5a; USER_DATA_SIZE], vmpl);
+        let mut buffer = vec![0u8; REPORT_RESPONSE_SIZE];
+        buffer[..REPORT_REQUEST_SIZE].copy_from_slice(&request.into_buffer());
+        (request, buffer)
+    }
+
+    fn response(status: u32) -> Vec<u8> {
+        let mut response = vec![0u8; REPORT_RESPONSE_SIZE];
+        response[..4].copy_from_slice(&status.to_le_bytes());
+        response[4..8].copy_from_slice(&REPORT_SIZE.to_le_bytes());
+        response
+    }
+
+    #[test]
+    fn test_report_rejects_non_vmpl0() {
+        let mock = set_mock_transport(MockGhcb::new());
+        let (_, mut buffer) = request_buffer(1);
+        assert!(matches!(
+            get_regular_report(&mut buffer),
+            Err(SvsmReqError::RequestError(
+                SvsmResultCode::INVALID_PARAMETER
+            ))
+        ));
+        assert_eq!(mock.request_count(), 0);
+    }
+
+    #[test]
+    fn test_report_success() {
+        let mut psp = MockGhcb::new();
+        psp.set_response(SnpGuestRequestMsgType::ReportRequest, &response(0));
+        let mock = set_mock_transport(psp);
+
+        let (request, mut buffer) = request_buffer(0);
+        assert_eq!(
+            get_regular_report(&mut buffer).unwrap(),
+            REPORT_RESPONSE_SIZE
+        );
+        mock.assert_request(&request);
+    }
+
+    #[test]
+    fn test_report_error_status() {
+        let mut psp = MockGhcb::new();
+        psp.set_response(SnpGuestRequestMsgType::ReportRequest, &response(0x16));
+        let _mock = set_mock_transport(psp);
+
+        let (_, mut buffer) = request_buffer(0);
+        assert!(matches!(
+            get_regular_report(&mut buffer),
+            Err(SvsmReqError::RequestError(SvsmResultCode::INVALID_REQUEST))
+        ));
+    }
+
+    #[test]
+    fn test_extended_report_rejects_oversized_certs() {
+        let mut psp = MockGhcb::new();
+        let too_small = GhcbError::CertsBufferTooSmall { required: u32::MAX };
+        psp.set_error(
+            SnpGuestRequestMsgType::ReportRequest,
+            SvsmReqError::FatalError(SvsmError::Ghcb(too_small)),
+        );
+        let mock = set_mock_transport(psp);
+
+        let (_, mut buffer) = request_buffer(0);
+        let err = get_extended_report_auto(&mut buffer).unwrap_err();
+        assert!(extract_required_cert_size(&err).unwrap() > SNP_GUEST_REQ_MAX_DATA_SIZE);
+        // No retry with the oversized buffer
+        assert_eq!(mock.request_count(), 1);
+    }
+}
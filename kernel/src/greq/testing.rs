@@ -0,0 +1,115 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2024 SUSE LLC
+
+//! Mock `SNP_GUEST_REQUEST` transport, so that the request services can be
+//! tested without a PSP.
+
+extern crate alloc;
+
+use crate::greq::msg::SnpGuestRequestMsgType;
+use crate::greq::pld_report::SnpReportRequest;
+use crate::locking::{LockGuard, SpinLock};
+use crate::protocols::errors::SvsmReqError;
+use alloc::vec::Vec;
+
+/// Serializes the tests using the mock transport.
+static MOCK_TRANSPORT_LOCK: SpinLock<()> = SpinLock::new(());
+static MOCK_TRANSPORT: SpinLock<Option<MockGhcb>> = SpinLock::new(None);
+
+/// Canned PSP responses, and the requests sent so far.
+#[derive(Debug, Default)]
+pub struct MockGhcb {
+    responses: Vec<(SnpGuestRequestMsgType, Vec<u8>)>,
+    errors: Vec<(SnpGuestRequestMsgType, SvsmReqError)>,
+    requests: Vec<(SnpGuestRequestMsgType, Vec<u8>)>,
+}
+
+impl MockGhcb {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the decrypted payload returned for requests of `msg_type`.
+    pub fn set_response(&mut self, msg_type: SnpGuestRequestMsgType, response: &[u8]) {
+        self.responses.retain(|(t, _)| *t != msg_type);
+        self.responses.push((msg_type, response.to_vec()));
+    }
+
+    /// Makes requests of `msg_type` fail with `err`, as if returned by the
+    /// hypervisor. Takes precedence over [`Self::set_response()`].
+    pub fn set_error(&mut self, msg_type: SnpGuestRequestMsgType, err: SvsmReqError) {
+        self.errors.retain(|(t, _)| *t != msg_type);
+        self.errors.push((msg_type, err));
+    }
+}
+
+/// Guard keeping a [`MockGhcb`] installed. The mock is removed when the
+/// guard is dropped.
+#[derive(Debug)]
+pub struct MockTransport<'a>(LockGuard<'a, ()>);
+
+impl MockTransport<'_> {
+    /// Returns the number of requests sent through the mock so far.
+    pub fn request_count(&self) -> usize {
+        MOCK_TRANSPORT
+            .lock()
+            .as_ref()
+            .map_or(0, |mock| mock.requests.len())
+    }
+
+    /// Asserts that the last request sent through the mock was `expected`.
+    pub fn assert_request(&self, expected: &SnpReportRequest) {
+        let mock = MOCK_TRANSPORT.lock();
+        let (msg_type, payload) = mock
+            .as_ref()
+            .and_then(|mock| mock.requests.last())
+            .expect("No request sent");
+        assert_eq!(*msg_type, SnpGuestRequestMsgType::ReportRequest);
+        assert_eq!(payload[..], expected.into_buffer());
+    }
+}
+
+impl Drop for MockTransport<'_> {
+    fn drop(&mut self) {
+        *MOCK_TRANSPORT.lock() = None;
+    }
+}
+
+/// Routes `SNP_GUEST_REQUEST` messages to `mock` until the returned guard
+/// is dropped.
+pub fn set_mock_transport(mock: MockGhcb) -> MockTransport<'static> {
+    let guard = MOCK_TRANSPORT_LOCK.lock();
+    *MOCK_TRANSPORT.lock() = Some(mock);
+    MockTransport(guard)
+}
+
+/// Sends a request through the installed mock, if any. The first
+/// `request_len` bytes of `buffer` hold the request payload, which is
+/// replaced by the canned response.
+///
+/// # Returns
+///
+/// `None` if no mock is installed, otherwise the length of the response.
+pub fn mock_send(
+    msg_type: SnpGuestRequestMsgType,
+    buffer: &mut [u8],
+    request_len: usize,
+) -> Option<Result<usize, SvsmReqError>> {
+    let mut cell = MOCK_TRANSPORT.lock();
+    let mock = cell.as_mut()?;
+
+    mock.requests
+        .push((msg_type, buffer[..request_len].to_vec()));
+    if let Some((_, err)) = mock.errors.iter().find(|(t, _)| *t == msg_type) {
+        return Some(Err(*err));
+    }
+    let Some((_, response)) = mock.responses.iter().find(|(t, _)| *t == msg_type) else {
+        return Some(Err(SvsmReqError::invalid_request()));
+    };
+    let Some(dst) = buffer.get_mut(..response.len()) else {
+        return Some(Err(SvsmReqError::invalid_parameter()));
+    };
+    dst.copy_from_slice(response);
+    Some(Ok(response.len()))
+}
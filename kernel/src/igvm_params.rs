@@ -11,6 +11,7 @@ use crate::address::{PhysAddr, VirtAddr};
 use crate::cpu::efer::EFERFlags;
 use crate::error::SvsmError;
 use crate::fw_meta::SevFWMetaData;
+use crate::mm::physmem::build_phys_mem_map;
 use crate::mm::PAGE_SIZE;
 use crate::utils::MemoryRegion;
 use alloc::vec::Vec;
@@ -18,7 +19,7 @@ use cpuarch::vmsa::VMSA;
 
 use bootlib::igvm_params::{IgvmGuestContext, IgvmParamBlock, IgvmParamPage};
 use core::mem::size_of;
-use igvm_defs::{IgvmEnvironmentInfo, MemoryMapEntryType, IGVM_VHS_MEMORY_MAP_ENTRY};
+use igvm_defs::{IgvmEnvironmentInfo, IGVM_VHS_MEMORY_MAP_ENTRY};
 
 const IGVM_MEMORY_ENTRIES_PER_PAGE: usize = PAGE_SIZE / size_of::<IGVM_VHS_MEMORY_MAP_ENTRY>();
 
@@ -63,7 +64,7 @@ impl IgvmParams<'_> {
     fn try_aligned_ref<'a, T>(addr: VirtAddr) -> Result<&'a T, SvsmError> {
         // SAFETY: we trust the caller to provide an address pointing to valid
         // memory which is not mutably aliased.
-        unsafe { addr.aligned_ref::<T>().ok_or(SvsmError::Firmware) }
+        unsafe { addr.as_aligned_ref::<T>().ok_or(SvsmError::Firmware) }
     }
 
     pub fn size(&self) -> usize {
@@ -99,46 +100,18 @@ impl IgvmParams<'_> {
         self.igvm_param_block.secrets_page as u64
     }
 
-    pub fn get_memory_regions(&self) -> Result<Vec<MemoryRegion<PhysAddr>>, SvsmError> {
-        // Count the number of memory entries present.  They must be
-        // non-overlapping and strictly increasing.
-        let mut number_of_entries = 0;
-        let mut next_page_number = 0;
-        for entry in self.igvm_memory_map.memory_map.iter() {
-            if entry.number_of_pages == 0 {
-                break;
-            }
-            if entry.starting_gpa_page_number < next_page_number {
-                return Err(SvsmError::Firmware);
-            }
-            let next_supplied_page_number = entry.starting_gpa_page_number + entry.number_of_pages;
-            if next_supplied_page_number < next_page_number {
-                return Err(SvsmError::Firmware);
-            }
-            next_page_number = next_supplied_page_number;
-            number_of_entries += 1;
-        }
-
-        // Now loop over the supplied entires and add a region for each
-        // known type.
-        let mut regions: Vec<MemoryRegion<PhysAddr>> = Vec::new();
-        for entry in self
-            .igvm_memory_map
-            .memory_map
+    /// Returns the populated entries of the IGVM memory map.
+    pub fn memory_map_entries(&self) -> &[IGVM_VHS_MEMORY_MAP_ENTRY] {
+        let entries = &self.igvm_memory_map.memory_map;
+        let count = entries
             .iter()
-            .take(number_of_entries)
-        {
-            if entry.entry_type == MemoryMapEntryType::MEMORY {
-                let starting_page: usize = entry.starting_gpa_page_number.try_into().unwrap();
-                let number_of_pages: usize = entry.number_of_pages.try_into().unwrap();
-                regions.push(MemoryRegion::new(
-                    PhysAddr::new(starting_page * PAGE_SIZE),
-                    number_of_pages * PAGE_SIZE,
-                ));
-            }
-        }
+            .position(|entry| entry.number_of_pages == 0)
+            .unwrap_or(entries.len());
+        &entries[..count]
+    }
 
-        Ok(regions)
+    pub fn get_memory_regions(&self) -> Result<Vec<MemoryRegion<PhysAddr>>, SvsmError> {
+        Ok(build_phys_mem_map(self)?.iter_ram().collect())
     }
 
     pub fn load_cpu_info(&self) -> Result<Vec<ACPICPUInfo>, SvsmError> {
@@ -161,6 +134,10 @@ impl IgvmParams<'_> {
         self.igvm_param_block.debug_serial_port
     }
 
+    pub fn service_policy(&self) -> u16 {
+        self.igvm_param_block.service_policy
+    }
+
     pub fn get_fw_metadata(&self) -> Option<SevFWMetaData> {
         if !self.should_launch_fw() {
             return None;
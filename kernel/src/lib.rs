@@ -12,11 +12,13 @@
 
 pub mod acpi;
 pub mod address;
+pub mod boot;
 pub mod config;
 pub mod console;
 pub mod cpu;
 pub mod crypto;
 pub mod debug;
+pub mod devices;
 pub mod error;
 pub mod fs;
 pub mod fw_cfg;
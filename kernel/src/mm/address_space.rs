@@ -6,6 +6,44 @@
 
 use crate::address::{PhysAddr, VirtAddr};
 use crate::utils::immut_after_init::ImmutAfterInitCell;
+use crate::utils::MemoryRegion;
+use core::ptr::addr_of;
+
+// Section boundaries defined in svsm.lds
+extern "C" {
+    static stext: u8;
+    static etext: u8;
+    static srodata: u8;
+    static erodata: u8;
+    static sdata: u8;
+    static edata: u8;
+    static sbss: u8;
+    static ebss: u8;
+}
+
+fn kernel_section(start: *const u8, end: *const u8) -> MemoryRegion<VirtAddr> {
+    MemoryRegion::from_kernel_symbols(start, end).expect("Invalid kernel section symbols")
+}
+
+/// Virtual address range of the SVSM kernel code
+pub fn text_region() -> MemoryRegion<VirtAddr> {
+    unsafe { kernel_section(addr_of!(stext), addr_of!(etext)) }
+}
+
+/// Virtual address range of the SVSM kernel read-only data
+pub fn rodata_region() -> MemoryRegion<VirtAddr> {
+    unsafe { kernel_section(addr_of!(srodata), addr_of!(erodata)) }
+}
+
+/// Virtual address range of the SVSM kernel initialized data
+pub fn data_region() -> MemoryRegion<VirtAddr> {
+    unsafe { kernel_section(addr_of!(sdata), addr_of!(edata)) }
+}
+
+/// Virtual address range of the SVSM kernel zero-initialized data
+pub fn bss_region() -> MemoryRegion<VirtAddr> {
+    unsafe { kernel_section(addr_of!(sbss), addr_of!(ebss)) }
+}
 
 #[derive(Copy, Clone)]
 #[allow(dead_code)]
@@ -164,6 +202,69 @@ pub const USER_MEM_START: VirtAddr = VirtAddr::new(0);
 /// End of user memory address range
 pub const USER_MEM_END: VirtAddr = USER_MEM_START.const_add(256 * SIZE_LEVEL3);
 
+/// Checks that `[base, base + size)` ends at or below `limit`.
+const fn fits_below(base: VirtAddr, size: usize, limit: VirtAddr) -> bool {
+    base.const_bits() + size <= limit.const_bits()
+}
+
+/// Checks that the regions defined above do not overlap. Evaluated at
+/// compile time, so a layout error fails the build.
+const fn verify_layout() {
+    // User memory covers the lower half of the level-3 page table, the
+    // SVSM regions each own a distinct entry in the upper half.
+    assert!(USER_MEM_START.const_bits() == 0);
+    assert!(PGTABLE_LVL3_IDX_PERTASK >= 256);
+    assert!(PGTABLE_LVL3_IDX_PERTASK != PGTABLE_LVL3_IDX_PERCPU);
+    assert!(PGTABLE_LVL3_IDX_PERTASK != PGTABLE_LVL3_IDX_SHARED);
+    assert!(PGTABLE_LVL3_IDX_PERCPU != PGTABLE_LVL3_IDX_SHARED);
+    assert!(PGTABLE_LVL3_IDX_PERCPU >= 256 && PGTABLE_LVL3_IDX_SHARED >= 256);
+
+    // Shared region, compared by last address as its end wraps to zero
+    assert!(SVSM_SHARED_STACK_BASE.const_bits() >= SVSM_SHARED_BASE.const_bits());
+    assert!(SVSM_SHARED_STACK_END.const_bits() > SVSM_SHARED_STACK_BASE.const_bits());
+    assert!(
+        SVSM_SHARED_STACK_END.const_bits() - 1 <= SVSM_SHARED_BASE.const_bits() + (SIZE_LEVEL3 - 1)
+    );
+
+    // Per-CPU region, in ascending order
+    assert!(fits_below(
+        SVSM_PERCPU_BASE,
+        PAGE_SIZE,
+        SVSM_PERCPU_CAA_BASE
+    ));
+    assert!(fits_below(
+        SVSM_PERCPU_CAA_BASE,
+        PAGE_SIZE,
+        SVSM_PERCPU_VMSA_BASE
+    ));
+    assert!(fits_below(
+        SVSM_PERCPU_VMSA_BASE,
+        PAGE_SIZE,
+        SVSM_PERCPU_STACKS_BASE
+    ));
+    assert!(fits_below(
+        SVSM_STACKS_INIT_TASK,
+        STACK_TOTAL_SIZE,
+        SVSM_STACKS_IST_BASE
+    ));
+    assert!(fits_below(
+        SVSM_STACK_IST_DF_BASE,
+        STACK_TOTAL_SIZE,
+        SVSM_PERCPU_TEMP_BASE
+    ));
+    assert!(SVSM_PERCPU_TEMP_END_4K.const_bits() <= SVSM_PERCPU_TEMP_BASE_2M.const_bits());
+    assert!(SVSM_PERCPU_TEMP_END_2M.const_bits() <= SVSM_PERCPU_END.const_bits());
+
+    // Per-task region
+    assert!(fits_below(
+        SVSM_PERTASK_STACK_BASE,
+        STACK_TOTAL_SIZE,
+        SVSM_PERTASK_END
+    ));
+}
+
+const _: () = verify_layout();
+
 #[cfg(test)]
 mod tests {
     use super::*;
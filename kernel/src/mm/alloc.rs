@@ -364,6 +364,20 @@ pub struct MemInfo {
     free_pages: [usize; MAX_ORDER],
 }
 
+impl MemInfo {
+    /// Returns the number of pages of the given `order`, or zero for an
+    /// order beyond `MAX_ORDER`.
+    pub fn total_pages(&self, order: usize) -> usize {
+        self.total_pages.get(order).copied().unwrap_or(0)
+    }
+
+    /// Returns the number of free pages of the given `order`, or zero for
+    /// an order beyond `MAX_ORDER`.
+    pub fn free_pages(&self, order: usize) -> usize {
+        self.free_pages.get(order).copied().unwrap_or(0)
+    }
+}
+
 /// Memory region with its physical/virtual addresses, page count, as well
 /// as other details.
 #[derive(Debug, Default)]
@@ -1277,7 +1291,7 @@ impl<const N: u16> SlabCommon<N> {
 
             let next_page = page.get_next_page();
             // Cannot fail with free slots on entry.
-            page = unsafe { next_page.aligned_mut().expect("Invalid next page") };
+            page = unsafe { next_page.as_aligned_mut().expect("Invalid next page") };
         }
     }
 
@@ -1302,7 +1316,7 @@ impl<const N: u16> SlabCommon<N> {
 
             let next_page = page.get_next_page();
             // Will fail if the object does not belong to this slab.
-            page = unsafe { next_page.aligned_mut().expect("Invalid next page") };
+            page = unsafe { next_page.as_aligned_mut().expect("Invalid next page") };
         }
     }
 
@@ -1313,7 +1327,7 @@ impl<const N: u16> SlabCommon<N> {
         loop {
             let slab_page = unsafe {
                 next_page_vaddr
-                    .aligned_mut::<SlabPage<N>>()
+                    .as_aligned_mut::<SlabPage<N>>()
                     .expect("couldn't find page to free")
             };
             next_page_vaddr = slab_page.get_next_page();
@@ -1468,6 +1482,22 @@ impl<const N: u16> Slab<N> {
         SLAB_PAGE_SLAB.lock().deallocate(slab_page.cast());
     }
 
+    /// Releases all completely free slab pages except one, regardless of
+    /// the usual shrinking threshold.
+    ///
+    /// # Returns
+    ///
+    /// The number of bytes given back to the page allocator.
+    fn reclaim(&mut self) -> usize {
+        let mut reclaimed = 0;
+        while self.common.free_pages > 1 {
+            let slab_page = self.common.free_one_page();
+            SLAB_PAGE_SLAB.lock().deallocate(slab_page.cast());
+            reclaimed += PAGE_SIZE;
+        }
+        reclaimed
+    }
+
     fn allocate(&mut self) -> Result<VirtAddr, AllocError> {
         self.grow_slab()?;
         Ok(self.common.allocate_slot())
@@ -1544,6 +1574,21 @@ impl SvsmAllocator {
         Some(())
     }
 
+    /// Returns free slab pages of all object sizes to the page allocator.
+    ///
+    /// # Returns
+    ///
+    /// The number of bytes reclaimed.
+    fn reclaim(&self) -> usize {
+        self.slab32.lock().reclaim()
+            + self.slab64.lock().reclaim()
+            + self.slab128.lock().reclaim()
+            + self.slab256.lock().reclaim()
+            + self.slab512.lock().reclaim()
+            + self.slab1024.lock().reclaim()
+            + self.slab2048.lock().reclaim()
+    }
+
     /// Resets the internal state. This is equivalent to reassigning `self`
     /// with a newly created [`SvsmAllocator`] with `Self::new()`.
     #[cfg(all(not(test_in_svsm), any(test, fuzzing)))]
@@ -1604,6 +1649,16 @@ unsafe impl GlobalAlloc for SvsmAllocator {
 #[cfg_attr(not(target_os = "none"), allow(dead_code))]
 static ALLOCATOR: SvsmAllocator = SvsmAllocator::new();
 
+/// Gives completely free slab pages back to the page allocator so that
+/// they become available for larger allocations again.
+///
+/// # Returns
+///
+/// The number of bytes reclaimed.
+pub fn reclaim_slab_pages() -> usize {
+    ALLOCATOR.reclaim()
+}
+
 /// Initializes the root memory region with the specified physical start
 /// address, virtual start address, and page count.
 pub fn root_mem_init(pstart: PhysAddr, vstart: VirtAddr, page_count: usize) {
@@ -0,0 +1,63 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2024 SUSE LLC
+
+use super::alloc::reclaim_slab_pages;
+use core::sync::atomic::{AtomicU64, Ordering};
+
+/// Counters describing the work done by [`heap_compact_pass()`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HeapCompactionStats {
+    pub passes: u64,
+    pub bytes_reclaimed: u64,
+}
+
+static PASSES: AtomicU64 = AtomicU64::new(0);
+static BYTES_RECLAIMED: AtomicU64 = AtomicU64::new(0);
+
+/// Runs one heap compaction pass.
+///
+/// Free pages in the page allocator are already merged with their buddies
+/// when they are released, so the only memory which is held back from
+/// large contiguous allocations are empty slab pages kept around by the
+/// slab shrinking heuristics. This pass hands those back to the page
+/// allocator, which in turn merges them.
+///
+/// # Returns
+///
+/// The number of bytes reclaimed by this pass.
+pub fn heap_compact_pass() -> usize {
+    let reclaimed = reclaim_slab_pages();
+
+    PASSES.fetch_add(1, Ordering::Relaxed);
+    BYTES_RECLAIMED.fetch_add(reclaimed as u64, Ordering::Relaxed);
+
+    if reclaimed != 0 {
+        log::debug!("Heap compaction reclaimed {} bytes", reclaimed);
+    }
+
+    reclaimed
+}
+
+/// Returns the accumulated compaction statistics.
+pub fn heap_compaction_stats() -> HeapCompactionStats {
+    HeapCompactionStats {
+        passes: PASSES.load(Ordering::Relaxed),
+        bytes_reclaimed: BYTES_RECLAIMED.load(Ordering::Relaxed),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compaction_stats() {
+        let before = heap_compaction_stats();
+        let reclaimed = heap_compact_pass();
+        let after = heap_compaction_stats();
+
+        assert!(after.passes > before.passes);
+        assert!(after.bytes_reclaimed >= before.bytes_reclaimed + reclaimed as u64);
+    }
+}
@@ -0,0 +1,80 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2024 SUSE LLC
+
+//! Validation and mapping of buffers whose guest physical address is
+//! supplied by the guest.
+
+use crate::address::{Address, PhysAddr, VirtAddr};
+use crate::error::SvsmError;
+use crate::mm::{writable_phys_addr, PerCPUPageMappingGuard};
+use crate::types::{PageSize, PAGE_SIZE};
+use crate::utils::MemoryRegion;
+
+/// A validated guest buffer, mapped into the SVSM address space for as
+/// long as this object lives.
+#[derive(Debug)]
+#[must_use = "if unused the mapping will immediately be unmapped"]
+pub struct GuestBufferMapping {
+    _guard: PerCPUPageMappingGuard,
+    vaddr: VirtAddr,
+}
+
+impl GuestBufferMapping {
+    /// Returns the virtual address of the start of the buffer.
+    pub fn virt_addr(&self) -> VirtAddr {
+        self.vaddr
+    }
+}
+
+/// Returns the page-aligned physical range covering `len` bytes at `gpa`,
+/// or `None` if the range wraps around the address space. An empty buffer
+/// covers the page containing `gpa`.
+fn guest_buffer_pages(gpa: PhysAddr, len: usize) -> Option<MemoryRegion<PhysAddr>> {
+    let end = gpa.bits().checked_add(len.max(1))?;
+    let end = end.checked_next_multiple_of(PAGE_SIZE)?;
+    let start = gpa.page_align();
+    Some(MemoryRegion::from_addresses(start, PhysAddr::from(end)))
+}
+
+/// Checks that all `len` bytes at `gpa` are writable guest memory and maps
+/// them.
+///
+/// Every page of the buffer must be part of the guest memory map, which
+/// excludes SVSM memory and MMIO ranges, and must neither be a VMSA nor lie
+/// in the ISA range.
+///
+/// # Returns
+///
+/// The mapping of the buffer, or `SvsmError::InvalidAddress` if the buffer
+/// wraps around the address space or a page is not writable guest memory.
+pub fn validate_guest_buffer(gpa: PhysAddr, len: usize) -> Result<GuestBufferMapping, SvsmError> {
+    let pages = guest_buffer_pages(gpa, len).ok_or(SvsmError::InvalidAddress)?;
+    if !pages.iter_pages(PageSize::Regular).all(writable_phys_addr) {
+        return Err(SvsmError::InvalidAddress);
+    }
+
+    let guard = PerCPUPageMappingGuard::create(pages.start(), pages.end(), 0)?;
+    let vaddr = guard.virt_addr() + gpa.page_offset();
+    Ok(GuestBufferMapping {
+        _guard: guard,
+        vaddr,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_guest_buffer_pages() {
+        let pages = guest_buffer_pages(PhysAddr::from(0x1ff0usize), 0x20).unwrap();
+        assert_eq!(pages.start(), PhysAddr::from(0x1000usize));
+        assert_eq!(pages.end(), PhysAddr::from(0x3000usize));
+
+        let pages = guest_buffer_pages(PhysAddr::from(0x1000usize), 0).unwrap();
+        assert_eq!(pages.len(), PAGE_SIZE);
+
+        assert!(guest_buffer_pages(PhysAddr::from(usize::MAX - 0xf), 0x20).is_none());
+    }
+}
@@ -7,7 +7,7 @@
 use crate::address::VirtAddr;
 use crate::error::SvsmError;
 use crate::fs::FileHandle;
-use crate::mm::vm::{Mapping, VMFileMapping, VMFileMappingFlags, VMalloc, VMR};
+use crate::mm::vm::{Mapping, VMDemandZero, VMFileMapping, VMFileMappingFlags, VMalloc, VMR};
 use crate::task::current_task;
 
 use core::ops::Deref;
@@ -61,6 +61,12 @@ pub fn create_anon_mapping(
     Ok(Arc::new(Mapping::new(alloc)))
 }
 
+/// Creates an anonymous mapping whose zeroed backing pages are allocated on
+/// first access rather than up front.
+pub fn create_demand_zero_mapping(size: usize, flags: VMFileMappingFlags) -> Arc<Mapping> {
+    Arc::new(VMDemandZero::new_mapping(size, flags))
+}
+
 pub fn mmap_user(
     addr: VirtAddr,
     file: Option<&FileHandle>,
@@ -6,11 +6,15 @@
 
 pub mod address_space;
 pub mod alloc;
+#[cfg(feature = "heap-compaction")]
+pub mod compaction;
+pub mod gpa_validate;
 pub mod guestmem;
 pub mod mappings;
 pub mod memory;
 pub mod page_visibility;
 pub mod pagetable;
+pub mod physmem;
 pub mod ptguards;
 pub mod stack;
 pub mod validate;
@@ -580,7 +580,7 @@ impl PageTable {
         phys: PhysAddr,
         flags: PTEntryFlags,
     ) -> Result<(), SvsmError> {
-        for addr in vregion.iter_pages(PageSize::Regular) {
+        for addr in vregion.pages_4k() {
             let offset = addr - vregion.start();
             self.map_4k(addr, phys + offset, flags)?;
         }
@@ -588,7 +588,7 @@ impl PageTable {
     }
 
     pub fn unmap_region_4k(&mut self, vregion: MemoryRegion<VirtAddr>) {
-        for addr in vregion.iter_pages(PageSize::Regular) {
+        for addr in vregion.pages_4k() {
             self.unmap_4k(addr);
         }
     }
@@ -599,7 +599,7 @@ impl PageTable {
         phys: PhysAddr,
         flags: PTEntryFlags,
     ) -> Result<(), SvsmError> {
-        for addr in vregion.iter_pages(PageSize::Huge) {
+        for addr in vregion.page_chunks(PAGE_SIZE_2M, false).map(|r| r.start()) {
             let offset = addr - vregion.start();
             self.map_2m(addr, phys + offset, flags)?;
         }
@@ -607,7 +607,7 @@ impl PageTable {
     }
 
     pub fn unmap_region_2m(&mut self, vregion: MemoryRegion<VirtAddr>) {
-        for addr in vregion.iter_pages(PageSize::Huge) {
+        for addr in vregion.page_chunks(PAGE_SIZE_2M, false).map(|r| r.start()) {
             self.unmap_2m(addr);
         }
     }
@@ -658,7 +658,7 @@ impl PageTable {
                     vaddr = vaddr + PAGE_SIZE_2M;
                 }
                 _ => {
-                    log::error!("Can't unmap - address not mapped {:#x}", vaddr);
+                    log::error!("Can't unmap - address not mapped {}", vaddr);
                 }
             }
         }
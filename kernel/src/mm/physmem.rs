@@ -0,0 +1,140 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2024 SUSE LLC
+
+extern crate alloc;
+
+use crate::address::PhysAddr;
+use crate::error::SvsmError;
+use crate::igvm_params::IgvmParams;
+use crate::types::PAGE_SIZE;
+use crate::utils::MemoryRegion;
+use alloc::vec::Vec;
+use igvm_defs::MemoryMapEntryType;
+
+/// Type of a range in the physical memory map.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PhysMemType {
+    Ram,
+    Reserved,
+    AcpiTable,
+    Firmware,
+    Mmio,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct PhysMemEntry {
+    pub region: MemoryRegion<PhysAddr>,
+    pub mem_type: PhysMemType,
+}
+
+/// The physical memory layout of the guest, sorted by address.
+#[derive(Clone, Debug, Default)]
+pub struct PhysMemMap {
+    entries: Vec<PhysMemEntry>,
+}
+
+impl PhysMemMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends an entry to the map. Entries must be added in increasing
+    /// address order and must not overlap.
+    pub fn push(&mut self, entry: PhysMemEntry) -> Result<(), SvsmError> {
+        if let Some(last) = self.entries.last() {
+            if entry.region.start() < last.region.end() {
+                return Err(SvsmError::Firmware);
+            }
+        }
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    pub fn entries(&self) -> &[PhysMemEntry] {
+        &self.entries
+    }
+
+    pub fn iter_ram(&self) -> impl Iterator<Item = MemoryRegion<PhysAddr>> + '_ {
+        self.entries
+            .iter()
+            .filter(|e| e.mem_type == PhysMemType::Ram)
+            .map(|e| e.region)
+    }
+
+    pub fn total_ram_bytes(&self) -> u64 {
+        self.iter_ram().map(|r| r.len() as u64).sum()
+    }
+}
+
+impl From<MemoryMapEntryType> for PhysMemType {
+    fn from(entry_type: MemoryMapEntryType) -> Self {
+        match entry_type {
+            MemoryMapEntryType::MEMORY => Self::Ram,
+            _ => Self::Reserved,
+        }
+    }
+}
+
+/// Builds the physical memory map from the memory map passed in the IGVM
+/// parameters.
+pub fn build_phys_mem_map(igvm_params: &IgvmParams<'_>) -> Result<PhysMemMap, SvsmError> {
+    let mut map = PhysMemMap::new();
+
+    for entry in igvm_params.memory_map_entries() {
+        let starting_page: usize = entry
+            .starting_gpa_page_number
+            .try_into()
+            .map_err(|_| SvsmError::Firmware)?;
+        let number_of_pages: usize = entry
+            .number_of_pages
+            .try_into()
+            .map_err(|_| SvsmError::Firmware)?;
+        let start = starting_page
+            .checked_mul(PAGE_SIZE)
+            .ok_or(SvsmError::Firmware)?;
+        let len = number_of_pages
+            .checked_mul(PAGE_SIZE)
+            .ok_or(SvsmError::Firmware)?;
+        let region =
+            MemoryRegion::checked_new(PhysAddr::from(start), len).ok_or(SvsmError::Firmware)?;
+
+        map.push(PhysMemEntry {
+            region,
+            mem_type: entry.entry_type.into(),
+        })?;
+    }
+
+    Ok(map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(start: usize, len: usize, mem_type: PhysMemType) -> PhysMemEntry {
+        PhysMemEntry {
+            region: MemoryRegion::new(PhysAddr::from(start), len),
+            mem_type,
+        }
+    }
+
+    #[test]
+    fn test_phys_mem_map_ram() {
+        let mut map = PhysMemMap::new();
+        map.push(entry(0, 0xa0000, PhysMemType::Ram)).unwrap();
+        map.push(entry(0xa0000, 0x60000, PhysMemType::Reserved))
+            .unwrap();
+        map.push(entry(0x100000, 0x100000, PhysMemType::Ram))
+            .unwrap();
+
+        assert_eq!(map.total_ram_bytes(), 0x1a0000);
+        assert_eq!(map.iter_ram().count(), 2);
+
+        // Overlapping or out of order entries are rejected.
+        assert!(map
+            .push(entry(0x180000, 0x1000, PhysMemType::Mmio))
+            .is_err());
+        assert_eq!(map.entries().len(), 3);
+    }
+}
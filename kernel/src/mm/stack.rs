@@ -14,7 +14,6 @@ use crate::mm::{phys_to_virt, virt_to_phys};
 use crate::mm::{
     STACK_PAGES, STACK_SIZE, STACK_TOTAL_SIZE, SVSM_SHARED_STACK_BASE, SVSM_SHARED_STACK_END,
 };
-use crate::types::PAGE_SIZE;
 use crate::utils::MemoryRegion;
 
 // Limit maximum number of stacks for now, address range support 2**16 8k stacks
@@ -81,10 +80,10 @@ static STACK_ALLOC: SpinLock<StackRange> = SpinLock::new(StackRange::new(
 
 pub fn allocate_stack_addr(stack: VirtAddr, pgtable: &mut PageTableRef) -> Result<(), SvsmError> {
     let flags = PTEntryFlags::data();
-    for i in 0..STACK_PAGES {
+    for addr in MemoryRegion::new(stack, STACK_SIZE).pages_4k() {
         let page = allocate_zeroed_page()?;
         let paddr = virt_to_phys(page);
-        pgtable.map_4k(stack + (i * PAGE_SIZE), paddr, flags)?;
+        pgtable.map_4k(addr, paddr, flags)?;
     }
 
     Ok(())
@@ -104,8 +103,8 @@ pub fn free_stack(stack: VirtAddr) {
     let mut pages: [VirtAddr; STACK_PAGES] = [VirtAddr::null(); STACK_PAGES];
 
     let mut pgtable = get_init_pgtable_locked();
-    for (i, page) in pages.iter_mut().enumerate() {
-        let addr = stack + (i * PAGE_SIZE);
+    let region = MemoryRegion::new(stack, STACK_SIZE);
+    for (page, addr) in pages.iter_mut().zip(region.pages_4k()) {
         let paddr = pgtable
             .phys_addr(addr)
             .expect("Failed to get stack physical address");
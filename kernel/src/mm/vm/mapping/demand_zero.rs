@@ -0,0 +1,122 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2024 SUSE LLC
+
+use crate::address::PhysAddr;
+use crate::error::SvsmError;
+use crate::mm::pagetable::PTEntryFlags;
+use crate::mm::vm::VMR;
+
+use super::rawalloc::RawAllocMapping;
+use super::{Mapping, VMFileMappingFlags, VMPageFaultResolution, VirtualMapping};
+
+/// Virtual mapping backed by zeroed pages which are only allocated when
+/// they are first accessed.
+///
+/// Pages come from the SVSM page allocator and are thus already validated.
+#[derive(Default, Debug)]
+pub struct VMDemandZero {
+    /// [`RawAllocMapping`] tracking the pages allocated so far
+    alloc: RawAllocMapping,
+    /// Page-table flags to map pages
+    flags: PTEntryFlags,
+}
+
+impl VMDemandZero {
+    /// Create a new instance without allocating any backing memory
+    ///
+    /// # Arguments
+    ///
+    /// * `size` - Size of the mapping. Must be aligned to PAGE_SIZE
+    pub fn new(size: usize, flags: VMFileMappingFlags) -> Self {
+        let mut demand = VMDemandZero {
+            alloc: RawAllocMapping::new(size),
+            flags: PTEntryFlags::ACCESSED | PTEntryFlags::DIRTY,
+        };
+
+        if flags.contains(VMFileMappingFlags::Write) {
+            demand.flags |= PTEntryFlags::WRITABLE;
+        }
+
+        if !flags.contains(VMFileMappingFlags::Execute) {
+            demand.flags |= PTEntryFlags::NX;
+        }
+
+        demand
+    }
+
+    /// Create a new [`Mapping`] of [`VMDemandZero`]
+    ///
+    /// # Arguments
+    ///
+    /// * `size` - Size of the mapping. Must be aligned to PAGE_SIZE
+    pub fn new_mapping(size: usize, flags: VMFileMappingFlags) -> Mapping {
+        Mapping::new(Self::new(size, flags))
+    }
+}
+
+impl VirtualMapping for VMDemandZero {
+    fn mapping_size(&self) -> usize {
+        self.alloc.mapping_size()
+    }
+
+    fn map(&self, offset: usize) -> Option<PhysAddr> {
+        self.alloc.map(offset)
+    }
+
+    fn unmap(&self, offset: usize) {
+        self.alloc.unmap(offset);
+    }
+
+    fn pt_flags(&self, _offset: usize) -> PTEntryFlags {
+        self.flags
+    }
+
+    fn handle_page_fault(
+        &mut self,
+        _vmr: &VMR,
+        offset: usize,
+        _write: bool,
+    ) -> Result<VMPageFaultResolution, SvsmError> {
+        self.alloc.alloc_page(offset)?;
+        let paddr = self.alloc.map(offset).ok_or(SvsmError::Mem)?;
+        Ok(VMPageFaultResolution {
+            paddr,
+            flags: self.flags,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::VirtAddr;
+    use crate::mm::alloc::{TestRootMem, DEFAULT_TEST_MEMORY_SIZE};
+    use crate::mm::vm::VMR_GRANULE;
+    use crate::types::PAGE_SIZE;
+
+    #[test]
+    fn test_demand_zero_alloc_on_fault() {
+        let _test_mem = TestRootMem::setup(DEFAULT_TEST_MEMORY_SIZE);
+        let vmr = VMR::new(
+            VirtAddr::from(0usize),
+            VirtAddr::from(VMR_GRANULE),
+            PTEntryFlags::empty(),
+        );
+        let mut demand = VMDemandZero::new(4 * PAGE_SIZE, VMFileMappingFlags::Write);
+        assert_eq!(demand.mapping_size(), 4 * PAGE_SIZE);
+        assert!(demand.map(PAGE_SIZE).is_none());
+
+        let res = demand.handle_page_fault(&vmr, PAGE_SIZE, true).unwrap();
+        assert_eq!(demand.map(PAGE_SIZE), Some(res.paddr));
+        assert!(res
+            .flags
+            .contains(PTEntryFlags::WRITABLE | PTEntryFlags::NX));
+        assert!(demand.map(0).is_none());
+
+        // Faults beyond the end of the mapping cannot be resolved.
+        assert!(demand
+            .handle_page_fault(&vmr, 4 * PAGE_SIZE, false)
+            .is_err());
+    }
+}
@@ -5,6 +5,7 @@
 // Author: Joerg Roedel <jroedel@suse.de>
 
 pub mod api;
+pub mod demand_zero;
 pub mod file_mapping;
 pub mod kernel_stack;
 pub mod phys_mem;
@@ -13,6 +14,7 @@ pub mod reserved;
 pub mod vmalloc;
 
 pub use api::{Mapping, VMMAdapter, VMPageFaultResolution, VirtualMapping, VMM};
+pub use demand_zero::VMDemandZero;
 pub use file_mapping::{VMFileMapping, VMFileMappingFlags};
 pub use kernel_stack::VMKernelStack;
 pub use phys_mem::VMPhysMem;
@@ -447,8 +447,9 @@ impl VMR {
     /// Notify the range that a page fault has occurred. This should be called from
     /// the page fault handler. The mappings withing this virtual memory region are
     /// examined and if they overlap with the page fault address then
-    /// [`VMR::handle_page_fault()`] is called to handle the page fault within that
-    /// range.
+    /// [`VirtualMapping::handle_page_fault()`](super::VirtualMapping::handle_page_fault)
+    /// is called to handle the page fault within that mapping. The page it
+    /// returns is mapped at the faulting address.
     ///
     /// # Arguments
     ///
@@ -461,7 +462,7 @@ impl VMR {
     /// '()' if the page fault was successfully handled.
     ///
     /// 'SvsmError::Mem' if the page fault should propogate to the next handler.
-    pub fn handle_page_fault(&self, vaddr: VirtAddr, _write: bool) -> Result<(), SvsmError> {
+    pub fn handle_page_fault(&self, vaddr: VirtAddr, write: bool) -> Result<(), SvsmError> {
         // Get the mapping that contains the faulting address and check if the
         // fault happened on a mapped part of the range.
 
@@ -474,7 +475,27 @@ impl VMR {
             return Err(SvsmError::Mem);
         }
 
-        Ok(())
+        let mut mapping = node.get_mapping_mut();
+        let page_size = mapping.page_size();
+        let offset = align_down(vaddr - start, usize::from(page_size));
+
+        // Pages which are already backed are mapped when the mapping is
+        // inserted, so the fault is spurious.
+        if mapping.map(offset).is_some() {
+            return Ok(());
+        }
+
+        let resolution = mapping.handle_page_fault(self, offset, write)?;
+        let (rstart, _) = self.virt_range();
+        let page = start + offset;
+        let idx = PageTable::index::<3>(VirtAddr::from(page - rstart));
+        let pt_flags = self.pt_flags | resolution.flags | PTEntryFlags::PRESENT;
+        let shared = mapping.shared();
+        let mut pgtbl_parts = self.pgtbl_parts.lock_write();
+        match page_size {
+            PageSize::Regular => pgtbl_parts[idx].map_4k(page, resolution.paddr, pt_flags, shared),
+            PageSize::Huge => pgtbl_parts[idx].map_2m(page, resolution.paddr, pt_flags, shared),
+        }
     }
 }
 
@@ -8,6 +8,7 @@ use crate::address::{PhysAddr, VirtAddr};
 use crate::cpu::cpuid::cpuid_table;
 use crate::cpu::ghcb::current_ghcb;
 use crate::cpu::percpu::PerCpu;
+use crate::cpu::snp_detect::snp_features_verify;
 use crate::error::SvsmError;
 use crate::io::IOPort;
 use crate::platform::{PageEncryptionMasks, PageStateChangeOp, SvsmPlatform};
@@ -16,7 +17,7 @@ use crate::sev::msr_protocol::verify_ghcb_version;
 use crate::sev::status::vtom_enabled;
 use crate::sev::{pvalidate_range, sev_status_init, sev_status_verify, PvalidateOp};
 use crate::svsm_console::SVSMIOPort;
-use crate::types::PageSize;
+use crate::types::{PageSize, GUEST_VMPL};
 use crate::utils::MemoryRegion;
 
 static CONSOLE_IO: SVSMIOPort = SVSMIOPort::new();
@@ -42,6 +43,7 @@ impl SvsmPlatform for SnpPlatform {
     }
 
     fn env_setup_late(&mut self) {
+        snp_features_verify(GUEST_VMPL as u8);
         sev_status_verify();
     }
 
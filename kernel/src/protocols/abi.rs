@@ -0,0 +1,59 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2024 SUSE LLC
+
+//! Guest-visible calling convention of SVSM services.
+//!
+//! # Issuing a call
+//!
+//! A guest running at a lower privileged VMPL requests a service by
+//! loading the arguments into its registers, setting the `call_pending`
+//! flag of its calling area and issuing a VMGEXIT with exit code
+//! `SVM_VMGEXIT_SNP_RUN_VMPL`, which switches to VMPL0. The calling area
+//! carries no arguments. The registers are read from the VMSA of the
+//! calling VMPL:
+//!
+//! * `RAX[63:32]` - protocol number, e.g.
+//!   [`SVSM_CORE_PROTOCOL`](super::SVSM_CORE_PROTOCOL)
+//! * `RAX[31:0]` - call identifier within the protocol
+//! * `RCX`, `RDX`, `R8` - call-specific arguments
+//!
+//! # Returning from a call
+//!
+//! On completion `RAX` holds the result code, see
+//! [`SvsmResultCode`](super::errors::SvsmResultCode). `RCX`, `RDX` and
+//! `R8` may hold call-specific results and are clobbered in any case. All
+//! other registers of the calling VMPL are left untouched.
+//!
+//! Result codes below `0x8000_0000` indicate success. Codes from
+//! `0x8000_0000` up to `0x8000_0fff` are defined for all protocols,
+//! codes starting at `0x8000_1000` are protocol-specific. A failing call
+//! has no side effects unless its protocol specifies otherwise.
+//!
+//! # Authorization
+//!
+//! Whether a VMPL may use a protocol is decided by the
+//! [policy](super::policy) before the request is dispatched. Calls from a
+//! VMPL which is not allowed fail with `INVALID_REQUEST`.
+//!
+//! # Version negotiation
+//!
+//! Every protocol has a range of supported versions. The guest queries it
+//! with `SVSM_REQ_CORE_QUERY_PROTOCOL`, passing the protocol number in
+//! `RCX[63:32]` and the version it wants to use in `RCX[31:0]`. If the
+//! version is supported, `RCX` returns the maximum supported version in
+//! bits 63:32 and the minimum in bits 31:0, otherwise it returns 0.
+//!
+//! # Stability
+//!
+//! The convention described here is stable. Changes which are not
+//! backwards compatible require an increment of [`SVSM_ABI_VERSION`].
+
+/// Version of the SVSM calling convention.
+pub const SVSM_ABI_VERSION: u32 = 1;
+
+/// Returns the version of the SVSM calling convention implemented by this
+/// SVSM.
+pub const fn abi_version() -> u32 {
+    SVSM_ABI_VERSION
+}
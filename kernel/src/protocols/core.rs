@@ -6,15 +6,18 @@
 
 use crate::address::{Address, PhysAddr, VirtAddr};
 use crate::cpu::flush_tlb_global_sync;
-use crate::cpu::percpu::{this_cpu_shared, PERCPU_AREAS, PERCPU_VMSAS};
+use crate::cpu::percpu::{this_cpu_shared, this_cpu_vmpl, PERCPU_AREAS, PERCPU_VMSAS};
 use crate::cpu::vmsa::{vmsa_mut_ref_from_vaddr, vmsa_ref_from_vaddr};
 use crate::error::SvsmError;
 use crate::locking::RWLock;
 use crate::mm::virtualrange::{VIRT_ALIGN_2M, VIRT_ALIGN_4K};
 use crate::mm::PerCPUPageMappingGuard;
 use crate::mm::{valid_phys_address, writable_phys_addr, GuestPtr};
+use crate::protocols::debug::{DEBUG_PROTOCOL_VERSION_MAX, DEBUG_PROTOCOL_VERSION_MIN};
 use crate::protocols::errors::SvsmReqError;
-use crate::protocols::RequestParams;
+use crate::protocols::info::{INFO_PROTOCOL_VERSION_MAX, INFO_PROTOCOL_VERSION_MIN};
+use crate::protocols::policy::{policy_check, SvsmService};
+use crate::protocols::{RequestParams, SVSM_DEBUG_PROTOCOL, SVSM_INFO_PROTOCOL};
 use crate::requests::SvsmCaa;
 use crate::sev::utils::{
     pvalidate, rmp_clear_guest_vmsa, rmp_grant_guest_access, rmp_revoke_guest_access,
@@ -211,6 +214,19 @@ fn core_query_protocol(params: &mut RequestParams) -> Result<(), SvsmReqError> {
             CORE_PROTOCOL_VERSION_MIN,
             CORE_PROTOCOL_VERSION_MAX,
         ),
+        SVSM_INFO_PROTOCOL => protocol_supported(
+            version,
+            INFO_PROTOCOL_VERSION_MIN,
+            INFO_PROTOCOL_VERSION_MAX,
+        ),
+        // The debug protocol is only reported to VMPLs allowed to use it.
+        SVSM_DEBUG_PROTOCOL if policy_check(SvsmService::Debug, this_cpu_vmpl()).is_ok() => {
+            protocol_supported(
+                version,
+                DEBUG_PROTOCOL_VERSION_MIN,
+                DEBUG_PROTOCOL_VERSION_MAX,
+            )
+        }
         _ => 0,
     };
 
@@ -251,7 +267,7 @@ fn core_pvalidate_one(entry: u64, flush: &mut bool) -> Result<(), SvsmReqError>
     }
 
     if !valid_phys_address(paddr) {
-        log::debug!("Invalid phys address: {:#x}", paddr);
+        log::debug!("Invalid phys address: {}", paddr);
         return Err(SvsmReqError::invalid_address());
     }
 
@@ -301,7 +317,7 @@ fn core_pvalidate_one(entry: u64, flush: &mut bool) -> Result<(), SvsmReqError>
             // no longer try to pvalidate MMIO memory.
             zero_mem_region(vaddr, vaddr + page_size_bytes);
         } else {
-            log::warn!("Not clearing possible read-only page at PA {:#x}", paddr);
+            log::warn!("Not clearing possible read-only page at PA {}", paddr);
         }
         rmp_grant_guest_access(vaddr, huge)?;
     }
@@ -391,6 +407,7 @@ fn core_remap_ca(params: &RequestParams) -> Result<(), SvsmReqError> {
 }
 
 pub fn core_protocol_request(request: u32, params: &mut RequestParams) -> Result<(), SvsmReqError> {
+    policy_check(SvsmService::Core, this_cpu_vmpl())?;
     match request {
         SVSM_REQ_CORE_REMAP_CA => core_remap_ca(params),
         SVSM_REQ_CORE_PVALIDATE => core_pvalidate(params),
@@ -0,0 +1,193 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2024 SUSE LLC
+
+//! SVSM debug protocol, exposing read-only kernel state for development
+//! and post-mortem debugging.
+//!
+//! Results are returned as a sequence of TLV records, each consisting of a
+//! little-endian `u16` tag, a little-endian `u16` value length and the
+//! value itself.
+
+extern crate alloc;
+
+use crate::address::{Address, PhysAddr};
+use crate::cpu::percpu::{this_cpu_vmpl, PERCPU_AREAS};
+use crate::mm::alloc::{memory_info, MAX_ORDER};
+use crate::mm::gpa_validate::validate_guest_buffer;
+use crate::mm::GuestPtr;
+use crate::protocols::policy::{policy_check, SvsmService};
+use crate::protocols::{errors::SvsmReqError, RequestParams};
+use crate::types::PAGE_SIZE;
+use alloc::vec::Vec;
+
+const SVSM_REQ_DEBUG_QUERY: u32 = 0;
+
+pub const DEBUG_PROTOCOL_VERSION_MIN: u32 = 1;
+pub const DEBUG_PROTOCOL_VERSION_MAX: u32 = 1;
+
+/// Value: order (`u32`), total pages (`u64`), free pages (`u64`).
+pub const DEBUG_TLV_HEAP_ORDER: u16 = 1;
+/// Value: APIC ID (`u32`), online flag (`u8`).
+pub const DEBUG_TLV_CPU_STATE: u16 = 2;
+/// Value: exit code (`u32`), count (`u64`).
+pub const DEBUG_TLV_VC_COUNT: u16 = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DebugQuery {
+    /// Page allocator statistics, one record per order.
+    HeapStats,
+    /// State of the CPU with the given APIC ID.
+    CpuState(u32),
+    /// Non-zero #VC counters of the CPU handling the request. Only
+    /// available with the `vc-stats` feature.
+    VcStats,
+    /// Not supported, the SVSM keeps no audit log.
+    AuditLog,
+}
+
+impl DebugQuery {
+    /// Decodes the query type from the low 32 bits of `r8` and its
+    /// argument from the high 32 bits.
+    pub fn from_raw(raw: u64) -> Option<Self> {
+        let arg = (raw >> 32) as u32;
+        match raw as u32 {
+            0 => Some(Self::HeapStats),
+            1 => Some(Self::CpuState(arg)),
+            2 => Some(Self::VcStats),
+            3 => Some(Self::AuditLog),
+            _ => None,
+        }
+    }
+}
+
+fn push_tlv(out: &mut Vec<u8>, tag: u16, fields: &[&[u8]]) {
+    let len: usize = fields.iter().map(|f| f.len()).sum();
+    out.extend_from_slice(&tag.to_le_bytes());
+    out.extend_from_slice(&(len as u16).to_le_bytes());
+    for field in fields {
+        out.extend_from_slice(field);
+    }
+}
+
+fn query_heap_stats(out: &mut Vec<u8>) {
+    let info = memory_info();
+    for order in 0..MAX_ORDER {
+        push_tlv(
+            out,
+            DEBUG_TLV_HEAP_ORDER,
+            &[
+                &(order as u32).to_le_bytes(),
+                &(info.total_pages(order) as u64).to_le_bytes(),
+                &(info.free_pages(order) as u64).to_le_bytes(),
+            ],
+        );
+    }
+}
+
+fn query_cpu_state(out: &mut Vec<u8>, apic_id: u32) -> Result<(), SvsmReqError> {
+    let cpu = PERCPU_AREAS
+        .get(apic_id)
+        .ok_or_else(SvsmReqError::invalid_parameter)?;
+    push_tlv(
+        out,
+        DEBUG_TLV_CPU_STATE,
+        &[&cpu.apic_id().to_le_bytes(), &[u8::from(cpu.is_online())]],
+    );
+    Ok(())
+}
+
+#[cfg(feature = "vc-stats")]
+fn query_vc_stats(out: &mut Vec<u8>) -> Result<(), SvsmReqError> {
+    use crate::cpu::percpu::this_cpu;
+    use crate::cpu::vc::stats::VC_STATS_EXIT_CODES;
+
+    let stats = this_cpu().vc_stats();
+    for code in 0..VC_STATS_EXIT_CODES {
+        let count = stats.count(code);
+        if count != 0 {
+            push_tlv(
+                out,
+                DEBUG_TLV_VC_COUNT,
+                &[&(code as u32).to_le_bytes(), &count.to_le_bytes()],
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "vc-stats"))]
+fn query_vc_stats(_out: &mut Vec<u8>) -> Result<(), SvsmReqError> {
+    Err(SvsmReqError::unsupported_call())
+}
+
+/// Runs a debug query and copies the result into a guest buffer.
+///
+/// `rcx` holds the guest physical address of the buffer and `rdx` its size
+/// in bytes. The buffer must not cross a page boundary. `r8` holds the
+/// query, see [`DebugQuery::from_raw()`]. On return `rcx` holds the full
+/// length of the result, which may be larger than the number of bytes
+/// copied.
+fn debug_query(params: &mut RequestParams) -> Result<(), SvsmReqError> {
+    let paddr = PhysAddr::from(params.rcx);
+    let size = params.rdx as usize;
+
+    if paddr.is_null() || size > PAGE_SIZE - paddr.page_offset() {
+        return Err(SvsmReqError::invalid_parameter());
+    }
+
+    let mapping = validate_guest_buffer(paddr, size)?;
+
+    let query = DebugQuery::from_raw(params.r8).ok_or_else(SvsmReqError::invalid_parameter)?;
+    let mut out = Vec::new();
+    match query {
+        DebugQuery::HeapStats => query_heap_stats(&mut out),
+        DebugQuery::CpuState(apic_id) => query_cpu_state(&mut out, apic_id)?,
+        DebugQuery::VcStats => query_vc_stats(&mut out)?,
+        DebugQuery::AuditLog => return Err(SvsmReqError::unsupported_call()),
+    }
+
+    let guest_buf = GuestPtr::<u8>::new(mapping.virt_addr());
+    for (i, byte) in out.iter().take(size).enumerate() {
+        guest_buf.offset(i as isize).write(*byte)?;
+    }
+
+    params.rcx = out.len() as u64;
+
+    Ok(())
+}
+
+pub fn debug_protocol_request(
+    request: u32,
+    params: &mut RequestParams,
+) -> Result<(), SvsmReqError> {
+    policy_check(SvsmService::Debug, this_cpu_vmpl())?;
+    match request {
+        SVSM_REQ_DEBUG_QUERY => debug_query(params),
+        _ => Err(SvsmReqError::unsupported_call()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debug_query_decode() {
+        assert_eq!(DebugQuery::from_raw(0), Some(DebugQuery::HeapStats));
+        assert_eq!(
+            DebugQuery::from_raw((7 << 32) | 1),
+            Some(DebugQuery::CpuState(7))
+        );
+        assert_eq!(DebugQuery::from_raw(2), Some(DebugQuery::VcStats));
+        assert_eq!(DebugQuery::from_raw(3), Some(DebugQuery::AuditLog));
+        assert_eq!(DebugQuery::from_raw(4), None);
+    }
+
+    #[test]
+    fn test_debug_tlv() {
+        let mut out = Vec::new();
+        push_tlv(&mut out, DEBUG_TLV_CPU_STATE, &[&3u32.to_le_bytes(), &[1]]);
+        assert_eq!(out, [2, 0, 5, 0, 3, 0, 0, 0, 1]);
+    }
+}
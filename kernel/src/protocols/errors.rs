@@ -5,6 +5,7 @@
 // Author: Joerg Roedel <jroedel@suse.de>
 
 use crate::error::SvsmError;
+use crate::mm::alloc::AllocError;
 
 #[derive(Debug, Clone, Copy)]
 #[allow(non_camel_case_types, dead_code, clippy::upper_case_acronyms)]
@@ -62,9 +63,21 @@ impl SvsmReqError {
     impl_req_err!(invalid_parameter, INVALID_PARAMETER);
     impl_req_err!(invalid_request, INVALID_REQUEST);
     impl_req_err!(busy, BUSY);
+    /// An operation did not complete in time. The SVSM specification has
+    /// no dedicated result code for this. It is not reported as BUSY since
+    /// retrying will not help: a timed out guest request disables VMPCK0.
+    pub fn timeout() -> Self {
+        Self::FatalError(SvsmError::Timeout)
+    }
     fn protocol(code: u64) -> Self {
         Self::RequestError(SvsmResultCode::PROTOCOL_BASE(code))
     }
+
+    /// Returns whether the guest may retry the request later, i.e. whether
+    /// it failed because the SVSM ran out of some resource.
+    pub fn is_retriable(&self) -> bool {
+        matches!(self, Self::RequestError(SvsmResultCode::BUSY))
+    }
 }
 
 impl From<SvsmError> for SvsmReqError {
@@ -75,8 +88,31 @@ impl From<SvsmError> for SvsmReqError {
             // to the guest as protocol-specific errors.
             SvsmError::SevSnp(e) => Self::protocol(e.ret()),
             SvsmError::InvalidAddress => Self::invalid_address(),
+            SvsmError::InvalidParameter => Self::invalid_parameter(),
+            // Resource exhaustion is reported as BUSY so that the guest can
+            // retry the request later.
+            SvsmError::ResourceBusy | SvsmError::Alloc(AllocError::OutOfMemory) => Self::busy(),
             // Use a fatal error for now
             _ => Self::FatalError(err),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_busy_is_retriable() {
+        let err = SvsmReqError::from(SvsmError::ResourceBusy);
+        assert!(err.is_retriable());
+        let SvsmReqError::RequestError(code) = err else {
+            panic!("ResourceBusy must not be fatal");
+        };
+        assert_eq!(u64::from(code), 0x8000_0007);
+
+        assert!(SvsmReqError::from(SvsmError::Alloc(AllocError::OutOfMemory)).is_retriable());
+        assert!(!SvsmReqError::invalid_request().is_retriable());
+        assert!(!SvsmReqError::from(SvsmError::Mem).is_retriable());
+    }
+}
@@ -0,0 +1,68 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2024 SUSE LLC
+
+//! SVSM information protocol, exposing details about the running SVSM
+//! build to the guest.
+
+use crate::address::{Address, PhysAddr};
+use crate::cpu::percpu::this_cpu_vmpl;
+use crate::mm::gpa_validate::validate_guest_buffer;
+use crate::mm::GuestPtr;
+use crate::protocols::policy::{policy_check, SvsmService};
+use crate::protocols::{errors::SvsmReqError, RequestParams};
+use crate::types::PAGE_SIZE;
+
+include!(concat!(env!("OUT_DIR"), "/version.rs"));
+
+const SVSM_REQ_INFO_GET_VERSION: u32 = 0;
+
+pub const INFO_PROTOCOL_VERSION_MIN: u32 = 1;
+pub const INFO_PROTOCOL_VERSION_MAX: u32 = 1;
+
+/// Copies the SVSM version string into a guest buffer.
+///
+/// `rcx` holds the guest physical address of the buffer and `rdx` its size
+/// in bytes. The buffer must lie in writable guest memory, so it may be
+/// neither a VMSA nor a page in the ISA range, and must not cross a page
+/// boundary. The string is not NUL-terminated; on return `rcx` holds the
+/// full length of the version string, which may be larger than the number
+/// of bytes copied.
+fn info_get_version(params: &mut RequestParams) -> Result<(), SvsmReqError> {
+    let paddr = PhysAddr::from(params.rcx);
+    let size = params.rdx as usize;
+
+    if paddr.is_null() || size > PAGE_SIZE - paddr.page_offset() {
+        return Err(SvsmReqError::invalid_parameter());
+    }
+
+    let mapping = validate_guest_buffer(paddr, size)?;
+
+    let version = SVSM_VERSION_STRING.as_bytes();
+    let guest_buf = GuestPtr::<u8>::new(mapping.virt_addr());
+    for (i, byte) in version.iter().take(size).enumerate() {
+        guest_buf.offset(i as isize).write(*byte)?;
+    }
+
+    params.rcx = version.len() as u64;
+
+    Ok(())
+}
+
+pub fn info_protocol_request(request: u32, params: &mut RequestParams) -> Result<(), SvsmReqError> {
+    policy_check(SvsmService::Info, this_cpu_vmpl())?;
+    match request {
+        SVSM_REQ_INFO_GET_VERSION => info_get_version(params),
+        _ => Err(SvsmReqError::unsupported_call()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_string() {
+        assert!(SVSM_VERSION_STRING.starts_with(env!("CARGO_PKG_VERSION")));
+    }
+}
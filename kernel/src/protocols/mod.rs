@@ -4,16 +4,25 @@
 //
 // Author: Dov Murik <dovmurik@linux.ibm.com>
 
+pub mod abi;
 pub mod core;
+pub mod debug;
 pub mod errors;
+pub mod info;
+pub mod policy;
 #[cfg(all(feature = "mstpm", not(test)))]
 pub mod vtpm;
 
 use cpuarch::vmsa::{GuestVMExit, VMSA};
 
+pub use abi::abi_version;
+
 // SVSM protocols
 pub const SVSM_CORE_PROTOCOL: u32 = 0;
 pub const SVSM_VTPM_PROTOCOL: u32 = 2;
+// Implementation-specific protocols
+pub const SVSM_INFO_PROTOCOL: u32 = 0x8000_0000;
+pub const SVSM_DEBUG_PROTOCOL: u32 = 0x8000_0001;
 
 #[derive(Debug, Default, Clone, Copy)]
 pub struct RequestParams {
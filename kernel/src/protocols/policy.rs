@@ -0,0 +1,158 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2024 SUSE LLC
+
+//! Per-protocol policy of which VMPLs may issue SVSM calls.
+
+use crate::protocols::errors::SvsmReqError;
+use crate::utils::immut_after_init::ImmutAfterInitCell;
+
+/// Set of VMPLs, one bit per VMPL.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VmplSet(u8);
+
+impl VmplSet {
+    pub const NONE: Self = Self(0);
+    pub const ALL: Self = Self(0xf);
+
+    pub const fn from_bits(bits: u8) -> Self {
+        Self(bits & Self::ALL.0)
+    }
+
+    pub const fn bits(&self) -> u8 {
+        self.0
+    }
+
+    pub const fn contains(&self, vmpl: u8) -> bool {
+        vmpl < 4 && self.0 & (1 << vmpl) != 0
+    }
+}
+
+/// SVSM services whose use is subject to the policy.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SvsmService {
+    Core,
+    Vtpm,
+    Info,
+    Debug,
+}
+
+impl SvsmService {
+    pub const ALL: [Self; 4] = [Self::Core, Self::Vtpm, Self::Info, Self::Debug];
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SecurityPolicy {
+    pub core_access: VmplSet,
+    pub vtpm_access: VmplSet,
+    pub info_access: VmplSet,
+    pub debug_access: VmplSet,
+}
+
+impl SecurityPolicy {
+    /// Policy allowing all services except the debug protocol to every
+    /// VMPL.
+    pub const DEFAULT: Self = Self {
+        core_access: VmplSet::ALL,
+        vtpm_access: VmplSet::ALL,
+        info_access: VmplSet::ALL,
+        debug_access: VmplSet::NONE,
+    };
+
+    /// Decodes the `service_policy` field of the IGVM parameter block.
+    /// Bits 0-3 hold the VMPLs allowed to use the core protocol, bits 4-7
+    /// those for the vTPM protocol, bits 8-11 those for the info protocol
+    /// and bits 12-15 those for the debug protocol. A value of zero selects
+    /// [`Self::DEFAULT`].
+    pub const fn from_raw(raw: u16) -> Self {
+        if raw == 0 {
+            return Self::DEFAULT;
+        }
+        Self {
+            core_access: VmplSet::from_bits(raw as u8),
+            vtpm_access: VmplSet::from_bits((raw >> 4) as u8),
+            info_access: VmplSet::from_bits((raw >> 8) as u8),
+            debug_access: VmplSet::from_bits((raw >> 12) as u8),
+        }
+    }
+
+    pub const fn access(&self, service: SvsmService) -> VmplSet {
+        match service {
+            SvsmService::Core => self.core_access,
+            SvsmService::Vtpm => self.vtpm_access,
+            SvsmService::Info => self.info_access,
+            SvsmService::Debug => self.debug_access,
+        }
+    }
+
+    pub fn check(&self, service: SvsmService, requesting_vmpl: u8) -> Result<(), SvsmReqError> {
+        if self.access(service).contains(requesting_vmpl) {
+            Ok(())
+        } else {
+            // The SVSM specification has no result code for a call the
+            // caller is not allowed to make.
+            Err(SvsmReqError::invalid_request())
+        }
+    }
+}
+
+static SECURITY_POLICY: ImmutAfterInitCell<SecurityPolicy> =
+    ImmutAfterInitCell::new(SecurityPolicy::DEFAULT);
+
+/// Installs the policy given by the raw `service_policy` value from the
+/// IGVM parameter block. Must be called before any request is processed.
+pub fn init_security_policy(raw: u16) {
+    let policy = SecurityPolicy::from_raw(raw);
+    SECURITY_POLICY
+        .reinit(&policy)
+        .expect("Failed to initialize the security policy");
+    if policy != SecurityPolicy::DEFAULT {
+        log::info!("SVSM security policy: {:?}", policy);
+    }
+}
+
+/// Checks whether `requesting_vmpl` may use `service`.
+///
+/// # Returns
+///
+/// `Err(SvsmReqError::invalid_request())` if the policy forbids the call.
+pub fn policy_check(service: SvsmService, requesting_vmpl: u8) -> Result<(), SvsmReqError> {
+    SECURITY_POLICY.check(service, requesting_vmpl)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy_default() {
+        assert_eq!(SecurityPolicy::from_raw(0), SecurityPolicy::DEFAULT);
+        for service in SvsmService::ALL {
+            for vmpl in 0..4 {
+                let allowed = service != SvsmService::Debug;
+                assert_eq!(
+                    SecurityPolicy::DEFAULT.check(service, vmpl).is_ok(),
+                    allowed
+                );
+            }
+            assert!(SecurityPolicy::DEFAULT.check(service, 4).is_err());
+        }
+    }
+
+    #[test]
+    fn test_policy_matrix() {
+        // Every combination of allowed VMPLs for each service
+        for bits in 0..16u16 {
+            for (i, service) in SvsmService::ALL.into_iter().enumerate() {
+                // Keep the other services non-zero so that the raw value
+                // never selects the default policy.
+                let raw = (0xffff & !(0xf << (4 * i))) | (bits << (4 * i));
+                let policy = SecurityPolicy::from_raw(raw);
+                for vmpl in 0..4u8 {
+                    let allowed = bits & (1 << vmpl) != 0;
+                    assert_eq!(policy.check(service, vmpl).is_ok(), allowed);
+                }
+            }
+        }
+    }
+}
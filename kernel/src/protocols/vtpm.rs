@@ -14,8 +14,13 @@ use alloc::vec::Vec;
 
 use crate::{
     address::{Address, PhysAddr},
-    mm::{valid_phys_address, GuestPtr, PerCPUPageMappingGuard},
-    protocols::{errors::SvsmReqError, RequestParams},
+    cpu::percpu::this_cpu_vmpl,
+    mm::{gpa_validate::validate_guest_buffer, GuestPtr},
+    protocols::{
+        errors::SvsmReqError,
+        policy::{policy_check, SvsmService},
+        RequestParams,
+    },
     types::PAGE_SIZE,
     vtpm::{vtpm_get_locked, MsTpmSimulatorInterface, VtpmProtocolInterface},
 };
@@ -212,17 +217,10 @@ fn vtpm_command_request(params: &RequestParams) -> Result<(), SvsmReqError> {
     if paddr.is_null() {
         return Err(SvsmReqError::invalid_parameter());
     }
-    if !valid_phys_address(paddr) {
-        return Err(SvsmReqError::invalid_address());
-    }
 
     // The vTPM buffer size is one page, but it not required to be page aligned.
-    let start = paddr.page_align();
-    let offset = paddr.page_offset();
-    let end = (paddr + PAGE_SIZE).page_align_up();
-
-    let guard = PerCPUPageMappingGuard::create(start, end, 0)?;
-    let vaddr = guard.virt_addr() + offset;
+    let mapping = validate_guest_buffer(paddr, PAGE_SIZE)?;
+    let vaddr = mapping.virt_addr();
 
     // vTPM common request/response structure (SVSM spec, table 15)
     //
@@ -249,6 +247,7 @@ fn vtpm_command_request(params: &RequestParams) -> Result<(), SvsmReqError> {
 }
 
 pub fn vtpm_protocol_request(request: u32, params: &mut RequestParams) -> Result<(), SvsmReqError> {
+    policy_check(SvsmService::Vtpm, this_cpu_vmpl())?;
     match request {
         SVSM_VTPM_QUERY => vtpm_query_request(params),
         SVSM_VTPM_COMMAND => vtpm_command_request(params),
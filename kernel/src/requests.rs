@@ -5,19 +5,24 @@
 // Author: Joerg Roedel <jroedel@suse.de>
 
 use crate::cpu::flush_tlb_global_sync;
-use crate::cpu::percpu::{process_requests, this_cpu, wait_for_requests};
+use crate::cpu::percpu::{process_requests, this_cpu, this_cpu_unsafe, wait_for_requests};
+use crate::cpu::vmsa::dump_vmsa;
 use crate::error::SvsmError;
 use crate::mm::GuestPtr;
 use crate::protocols::core::core_protocol_request;
+use crate::protocols::debug::debug_protocol_request;
 use crate::protocols::errors::{SvsmReqError, SvsmResultCode};
+use crate::protocols::info::info_protocol_request;
 use crate::sev::ghcb::switch_to_vmpl;
 
 #[cfg(all(feature = "mstpm", not(test)))]
 use crate::protocols::{vtpm::vtpm_protocol_request, SVSM_VTPM_PROTOCOL};
-use crate::protocols::{RequestParams, SVSM_CORE_PROTOCOL};
+use crate::protocols::{
+    RequestParams, SVSM_CORE_PROTOCOL, SVSM_DEBUG_PROTOCOL, SVSM_INFO_PROTOCOL,
+};
 use crate::types::GUEST_VMPL;
 use crate::utils::halt;
-use cpuarch::vmsa::GuestVMExit;
+use cpuarch::vmsa::{GuestVMExit, VMSA};
 
 /// The SVSM Calling Area (CAA)
 #[repr(C, packed)]
@@ -97,10 +102,53 @@ fn request_loop_once(
         SVSM_CORE_PROTOCOL => core_protocol_request(request, params).map(|_| true),
         #[cfg(all(feature = "mstpm", not(test)))]
         SVSM_VTPM_PROTOCOL => vtpm_protocol_request(request, params).map(|_| true),
+        SVSM_INFO_PROTOCOL => info_protocol_request(request, params).map(|_| true),
+        SVSM_DEBUG_PROTOCOL => debug_protocol_request(request, params).map(|_| true),
         _ => Err(SvsmReqError::unsupported_protocol()),
     }
 }
 
+/// Register state handed back to the calling VMPL when an SVSM call
+/// completes.
+///
+/// Following the SVSM calling convention the result code is returned in
+/// RAX, and RCX, RDX and R8 carry request-specific inputs and outputs, so
+/// the caller must treat these four registers as clobbered. All other
+/// registers are callee-saved: the SVSM never modifies them in the VMSA.
+#[derive(Debug, Clone, Copy)]
+pub struct SvsmReturnContext {
+    pub rax: u64,
+    pub params: RequestParams,
+}
+
+impl SvsmReturnContext {
+    /// Stores the return values in the VMSA of the calling VMPL `vmpl`.
+    pub fn write_back(&self, vmsa: &mut VMSA, vmpl: u8) {
+        debug_assert_eq!({ vmsa.vmpl }, vmpl, "VMSA does not belong to VMPL {vmpl}");
+        vmsa.rax = self.rax;
+        self.params.write_back(vmsa);
+    }
+}
+
+/// Makes the VMSA of `vmpl` runnable again and switches to it. Returns when
+/// the guest issues the next SVSM call or otherwise exits to the SVSM.
+fn vmpl_return_to(vmpl: u8) {
+    // Make VMSA runnable again by setting EFER.SVME.  This requires a
+    // separate scope so the CPU reference does not outlive the use of
+    // the VMSA reference.
+    {
+        let cpu = this_cpu();
+        let mut vmsa_ref = cpu.guest_vmsa_ref();
+        let vmsa = vmsa_ref.vmsa();
+        debug_assert_eq!({ vmsa.vmpl }, vmpl, "VMSA does not belong to VMPL {vmpl}");
+        vmsa.enable();
+    }
+
+    flush_tlb_global_sync();
+
+    switch_to_vmpl(vmpl.into());
+}
+
 fn check_requests() -> Result<bool, SvsmReqError> {
     let cpu = this_cpu();
     let vmsa_ref = cpu.guest_vmsa_ref();
@@ -120,19 +168,7 @@ pub fn request_loop() {
         // the guest to execute.  When halting, assume that the hypervisor
         // will schedule the guest VMPL on its own.
         if update_mappings().is_ok() {
-            // Make VMSA runnable again by setting EFER.SVME.  This requires a
-            // separate scope so the CPU reference does not outlive the use of
-            // the VMSA reference.
-            {
-                let cpu = this_cpu();
-                let mut vmsa_ref = cpu.guest_vmsa_ref();
-                let vmsa = vmsa_ref.vmsa();
-                vmsa.enable();
-            }
-
-            flush_tlb_global_sync();
-
-            switch_to_vmpl(GUEST_VMPL as u32);
+            vmpl_return_to(GUEST_VMPL as u8);
         } else {
             loop {
                 log::debug!("No VMSA or CAA! Halting");
@@ -205,6 +241,9 @@ pub extern "C" fn request_processing_main() {
             // Clear EFER.SVME in guest VMSA
             vmsa.disable();
 
+            // Record the caller for the policy checks of the request handlers
+            unsafe { (*this_cpu_unsafe()).set_current_vmpl(vmsa.vmpl) };
+
             rax = vmsa.rax;
             RequestInfo {
                 protocol: (rax >> 32) as u32,
@@ -237,6 +276,9 @@ pub extern "C" fn request_processing_main() {
                     request_info.request,
                     err
                 );
+                let cpu = this_cpu();
+                let mut vmsa_ref = cpu.guest_vmsa_ref();
+                dump_vmsa(vmsa_ref.vmsa(), apic_id);
                 break;
             }
         };
@@ -246,8 +288,11 @@ pub extern "C" fn request_processing_main() {
             let cpu = this_cpu();
             let mut vmsa_ref = cpu.guest_vmsa_ref();
             let vmsa = vmsa_ref.vmsa();
-            vmsa.rax = rax;
-            request_info.params.write_back(vmsa);
+            let ret = SvsmReturnContext {
+                rax,
+                params: request_info.params,
+            };
+            ret.write_back(vmsa, GUEST_VMPL as u8);
         }
     }
 
@@ -18,7 +18,7 @@ use crate::platform::PageStateChangeOp;
 use crate::sev::hv_doorbell::HVDoorbell;
 use crate::sev::sev_snp_enabled;
 use crate::sev::utils::raw_vmgexit;
-use crate::types::{PageSize, PAGE_SIZE_2M};
+use crate::types::{PageSize, PAGE_SIZE, PAGE_SIZE_2M};
 use crate::utils::MemoryRegion;
 
 use core::arch::global_asm;
@@ -110,6 +110,74 @@ pub enum GhcbError {
     VmgexitInvalid,
     // A response from the hypervisor included an error code
     VmgexitError(u64, u64),
+    // The hypervisor returned an undefined value in SW_EXITINFO1
+    SwExitInfoInvalid(u64),
+    // The hypervisor rejected the request because of missing or invalid
+    // input
+    InvalidParameter,
+    // The hypervisor is busy, the request can be resent
+    Busy,
+    // The certificate buffer of an extended guest request is too small,
+    // `required` is the number of pages needed
+    CertsBufferTooSmall { required: u32 },
+    // The PSP firmware completed a guest request with an error status
+    PspError(u32),
+}
+
+// VMGEXIT completion values in SW_EXITINFO1[31:0]
+const VMGEXIT_SUCCESS: u64 = 0;
+const VMGEXIT_ERROR: u64 = 1;
+const VMGEXIT_EXCEPTION: u64 = 2;
+
+// Error codes in SW_EXITINFO2 when SW_EXITINFO1[31:0] is VMGEXIT_ERROR
+const GHCB_ERR_MISSING_INPUT: u64 = 4;
+const GHCB_ERR_INVALID_INPUT: u64 = 5;
+
+// Hypervisor error codes in SW_EXITINFO2[63:32] of guest requests
+const GUEST_REQ_ERR_INVALID_LEN: u64 = 1;
+const GUEST_REQ_ERR_BUSY: u64 = 2;
+
+impl GhcbError {
+    /// Classifies a failed VMGEXIT from the SW_EXITINFO1 and SW_EXITINFO2
+    /// values returned by the hypervisor.
+    fn from_exit_info(info1: u64, info2: u64) -> Self {
+        match (info1 & 0xffff_ffff, info2) {
+            (VMGEXIT_ERROR, GHCB_ERR_MISSING_INPUT | GHCB_ERR_INVALID_INPUT) => {
+                Self::InvalidParameter
+            }
+            (VMGEXIT_ERROR | VMGEXIT_EXCEPTION, _) => Self::VmgexitError(info1, info2),
+            _ => Self::SwExitInfoInvalid(info1),
+        }
+    }
+
+    /// Classifies a failed guest request from its non-zero SW_EXITINFO2
+    /// value. `rbx` holds the number of certificate pages required when the
+    /// certificate buffer of an extended request is too small.
+    fn from_guest_request(info2: u64, rbx: u64) -> Self {
+        match info2 >> 32 {
+            0 => Self::PspError(info2 as u32),
+            GUEST_REQ_ERR_INVALID_LEN => Self::CertsBufferTooSmall {
+                required: rbx as u32,
+            },
+            GUEST_REQ_ERR_BUSY => Self::Busy,
+            _ => Self::VmgexitError(rbx, info2),
+        }
+    }
+
+    /// Returns whether the error reports a certificate buffer that is too
+    /// small for an extended guest request.
+    pub const fn is_cert_too_small(&self) -> bool {
+        matches!(self, Self::CertsBufferTooSmall { .. })
+    }
+
+    /// Returns the certificate buffer size in bytes required by the
+    /// hypervisor if the error is [`Self::CertsBufferTooSmall`].
+    pub const fn required_cert_size(&self) -> Option<usize> {
+        match self {
+            Self::CertsBufferTooSmall { required } => Some(*required as usize * PAGE_SIZE),
+            _ => None,
+        }
+    }
 }
 
 impl From<GhcbError> for SvsmError {
@@ -341,8 +409,10 @@ impl GHCB {
         raw_vmgexit();
 
         let sw_exit_info_1 = self.get_exit_info_1_valid()?;
-        if sw_exit_info_1 != 0 {
-            return Err(GhcbError::VmgexitError(sw_exit_info_1, self.sw_exit_info_2));
+        if sw_exit_info_1 != VMGEXIT_SUCCESS {
+            // Only trust exit_info_2 if the hypervisor marked it valid
+            let sw_exit_info_2 = self.get_exit_info_2_valid().unwrap_or(0);
+            return Err(GhcbError::from_exit_info(sw_exit_info_1, sw_exit_info_2));
         }
 
         Ok(())
@@ -549,7 +619,7 @@ impl GHCB {
 
         let sw_exit_info_2 = self.get_exit_info_2_valid()?;
         if sw_exit_info_2 != 0 {
-            return Err(GhcbError::VmgexitError(self.sw_exit_info_1, sw_exit_info_2).into());
+            return Err(GhcbError::from_guest_request(sw_exit_info_2, 0).into());
         }
 
         Ok(())
@@ -579,7 +649,13 @@ impl GHCB {
         // For an extended request, if the buffer provided is too small, the hypervisor
         // will return in RBX the number of contiguous pages required
         if sw_exit_info_2 != 0 {
-            return Err(GhcbError::VmgexitError(self.rbx, sw_exit_info_2).into());
+            let rbx = self.get_rbx_valid();
+            let err = GhcbError::from_guest_request(sw_exit_info_2, rbx.unwrap_or(0));
+            // The required size is meaningless unless RBX was returned valid
+            if err.is_cert_too_small() && rbx.is_err() {
+                return Err(GhcbError::VmgexitInvalid.into());
+            }
+            return Err(err.into());
         }
 
         Ok(())
@@ -692,4 +768,53 @@ mod tests {
         assert_eq!(offset_of!(GHCB, usage), 0xffc);
         assert_eq!(mem::size_of::<GHCB>(), 0x1000);
     }
+
+    #[test]
+    fn test_exit_info_errors() {
+        assert!(matches!(
+            GhcbError::from_exit_info(1, GHCB_ERR_INVALID_INPUT),
+            GhcbError::InvalidParameter
+        ));
+        assert!(matches!(
+            GhcbError::from_exit_info(1, GHCB_ERR_MISSING_INPUT),
+            GhcbError::InvalidParameter
+        ));
+        assert!(matches!(
+            GhcbError::from_exit_info(2, 0x8000_0b0e),
+            GhcbError::VmgexitError(2, 0x8000_0b0e)
+        ));
+        assert!(matches!(
+            GhcbError::from_exit_info(7, 0),
+            GhcbError::SwExitInfoInvalid(7)
+        ));
+    }
+
+    #[test]
+    fn test_guest_request_errors() {
+        assert!(matches!(
+            GhcbError::from_guest_request(0x16, 0),
+            GhcbError::PspError(0x16)
+        ));
+        assert!(matches!(
+            GhcbError::from_guest_request(1 << 32, 4),
+            GhcbError::CertsBufferTooSmall { required: 4 }
+        ));
+        assert!(matches!(
+            GhcbError::from_guest_request(2 << 32, 0),
+            GhcbError::Busy
+        ));
+        assert!(matches!(
+            GhcbError::from_guest_request(3 << 32, 0),
+            GhcbError::VmgexitError(0, 0x3_0000_0000)
+        ));
+    }
+
+    #[test]
+    fn test_required_cert_size() {
+        let too_small = GhcbError::CertsBufferTooSmall { required: 4 };
+        assert!(too_small.is_cert_too_small());
+        assert_eq!(too_small.required_cert_size(), Some(4 * PAGE_SIZE));
+        assert!(!GhcbError::Busy.is_cert_too_small());
+        assert_eq!(GhcbError::Busy.required_cert_size(), None);
+    }
 }
@@ -5,10 +5,10 @@
 // Author: Joerg Roedel <jroedel@suse.de>
 
 use crate::address::{Address, PhysAddr};
+use crate::cpu::cpuid::CpuidResult;
 use crate::cpu::msr::{read_msr, write_msr, SEV_GHCB};
 use crate::error::SvsmError;
 use crate::utils::halt;
-use crate::utils::immut_after_init::ImmutAfterInitCell;
 
 use super::utils::raw_vmgexit;
 
@@ -38,6 +38,10 @@ pub enum GHCBMsr {}
 impl GHCBMsr {
     pub const SEV_INFO_REQ: u64 = 0x02;
     pub const SEV_INFO_RESP: u64 = 0x01;
+    pub const CPUID_REQ: u64 = 0x04;
+    pub const CPUID_RESP: u64 = 0x05;
+    pub const AP_RESET_HOLD_REQ: u64 = 0x06;
+    pub const AP_RESET_HOLD_RESP: u64 = 0x07;
     pub const SNP_REG_GHCB_GPA_REQ: u64 = 0x12;
     pub const SNP_REG_GHCB_GPA_RESP: u64 = 0x13;
     pub const SNP_STATE_CHANGE_REQ: u64 = 0x14;
@@ -66,8 +70,6 @@ impl Display for GHCBHvFeatures {
     }
 }
 
-static GHCB_HV_FEATURES: ImmutAfterInitCell<GHCBHvFeatures> = ImmutAfterInitCell::uninit();
-
 /// Check that we support the hypervisor's advertised GHCB versions.
 pub fn verify_ghcb_version() {
     // Request SEV information.
@@ -94,41 +96,6 @@ pub fn verify_ghcb_version() {
     );
 }
 
-pub fn hypervisor_ghcb_features() -> GHCBHvFeatures {
-    *GHCB_HV_FEATURES
-}
-
-pub fn init_hypervisor_ghcb_features() -> Result<(), GhcbMsrError> {
-    write_msr(SEV_GHCB, GHCBMsr::SNP_HV_FEATURES_REQ);
-    raw_vmgexit();
-    let result = read_msr(SEV_GHCB);
-    if (result & 0xFFF) == GHCBMsr::SNP_HV_FEATURES_RESP {
-        let features = GHCBHvFeatures::from_bits_truncate(result >> 12);
-
-        // Verify that the required features are supported.
-        let required = GHCBHvFeatures::SEV_SNP
-            | GHCBHvFeatures::SEV_SNP_AP_CREATION
-            | GHCBHvFeatures::SEV_SNP_MULTI_VMPL;
-        let missing = !features & required;
-        if !missing.is_empty() {
-            log::error!(
-                "Required hypervisor GHCB features not available: present={:#x}, required={:#x}, missing={:#x}",
-                features, required, missing
-            );
-            // FIXME - enforce this panic once KVM advertises the required
-            // features.
-            // panic!("Required hypervisor GHCB features not available");
-        }
-
-        GHCB_HV_FEATURES
-            .init(&features)
-            .expect("Already initialized GHCB HV features");
-        Ok(())
-    } else {
-        Err(GhcbMsrError::InfoMismatch)
-    }
-}
-
 pub fn register_ghcb_gpa_msr(addr: PhysAddr) -> Result<(), GhcbMsrError> {
     let mut info = addr.bits() as u64;
 
@@ -148,6 +115,58 @@ pub fn register_ghcb_gpa_msr(addr: PhysAddr) -> Result<(), GhcbMsrError> {
     Ok(())
 }
 
+fn cpuid_msr_one(leaf: u32, reg: u64) -> Result<u32, GhcbMsrError> {
+    let info: u64 = (u64::from(leaf) << 32) | (reg << 30) | GHCBMsr::CPUID_REQ;
+
+    write_msr(SEV_GHCB, info);
+    raw_vmgexit();
+    let response = read_msr(SEV_GHCB);
+
+    if (response & 0xfff) != GHCBMsr::CPUID_RESP {
+        return Err(GhcbMsrError::InfoMismatch);
+    }
+
+    if ((response >> 30) & 0x3) != reg {
+        return Err(GhcbMsrError::DataMismatch);
+    }
+
+    Ok((response >> 32) as u32)
+}
+
+/// Queries a CPUID leaf from the hypervisor through the GHCB MSR protocol.
+///
+/// The MSR protocol returns one register per request and does not support
+/// sub-leaves, so only leaves without an ECX index can be queried. The
+/// values returned are provided by the untrusted hypervisor and must not
+/// be used where the SNP CPUID table is authoritative.
+pub fn cpuid_msr(leaf: u32) -> Result<CpuidResult, GhcbMsrError> {
+    Ok(CpuidResult {
+        eax: cpuid_msr_one(leaf, 0)?,
+        ebx: cpuid_msr_one(leaf, 1)?,
+        ecx: cpuid_msr_one(leaf, 2)?,
+        edx: cpuid_msr_one(leaf, 3)?,
+    })
+}
+
+/// Parks the current CPU in the hypervisor until it gets woken up again,
+/// using the GHCB MSR protocol AP reset hold request.
+pub fn ap_reset_hold_msr() -> Result<(), GhcbMsrError> {
+    loop {
+        write_msr(SEV_GHCB, GHCBMsr::AP_RESET_HOLD_REQ);
+        raw_vmgexit();
+        let response = read_msr(SEV_GHCB);
+
+        if (response & 0xfff) != GHCBMsr::AP_RESET_HOLD_RESP {
+            return Err(GhcbMsrError::InfoMismatch);
+        }
+
+        // A zero value means the hold has not been released yet.
+        if (response >> 12) != 0 {
+            return Ok(());
+        }
+    }
+}
+
 fn set_page_valid_status_msr(addr: PhysAddr, valid: bool) -> Result<(), GhcbMsrError> {
     let mut info: u64 = (addr.bits() as u64) & 0x000f_ffff_ffff_f000;
 
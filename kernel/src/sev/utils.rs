@@ -17,6 +17,8 @@ pub enum SevSnpError {
     FAIL_INPUT(u64),
     FAIL_PERMISSION(u64),
     FAIL_SIZEMISMATCH(u64),
+    // A return value not documented for the instruction
+    FAIL_UNKNOWN(u64),
     // Not a real error value, but we want to keep track of this,
     // especially for protocol-specific messaging
     FAIL_UNCHANGED(u64),
@@ -35,7 +37,8 @@ impl SevSnpError {
             Self::FAIL_INPUT(ret)
             | Self::FAIL_UNCHANGED(ret)
             | Self::FAIL_PERMISSION(ret)
-            | Self::FAIL_SIZEMISMATCH(ret) => *ret,
+            | Self::FAIL_SIZEMISMATCH(ret)
+            | Self::FAIL_UNKNOWN(ret) => *ret,
         }
     }
 }
@@ -47,10 +50,22 @@ impl fmt::Display for SevSnpError {
             Self::FAIL_UNCHANGED(_) => write!(f, "FAIL_UNCHANGED"),
             Self::FAIL_PERMISSION(_) => write!(f, "FAIL_PERMISSION"),
             Self::FAIL_SIZEMISMATCH(_) => write!(f, "FAIL_SIZEMISMATCH"),
+            Self::FAIL_UNKNOWN(ret) => write!(f, "FAIL_UNKNOWN({:#x})", ret),
         }
     }
 }
 
+/// Decodes the value returned in RAX by PVALIDATE and RMPADJUST.
+fn rmp_insn_result(ret: u64) -> Result<(), SevSnpError> {
+    match ret {
+        0 => Ok(()),
+        1 => Err(SevSnpError::FAIL_INPUT(ret)),
+        2 => Err(SevSnpError::FAIL_PERMISSION(ret)),
+        6 => Err(SevSnpError::FAIL_SIZEMISMATCH(ret)),
+        _ => Err(SevSnpError::FAIL_UNKNOWN(ret)),
+    }
+}
+
 fn pvalidate_range_4k(region: MemoryRegion<VirtAddr>, valid: PvalidateOp) -> Result<(), SvsmError> {
     for addr in region.iter_pages(PageSize::Regular) {
         pvalidate(addr, PageSize::Regular, valid)?;
@@ -95,6 +110,11 @@ pub enum PvalidateOp {
 }
 
 pub fn pvalidate(vaddr: VirtAddr, size: PageSize, valid: PvalidateOp) -> Result<(), SvsmError> {
+    debug_assert!(
+        vaddr.is_aligned(size.into()),
+        "Misaligned PVALIDATE at {:#x}",
+        vaddr
+    );
     let rax = vaddr.bits();
     let rcx: u64 = match size {
         PageSize::Regular => 0,
@@ -120,16 +140,18 @@ pub fn pvalidate(vaddr: VirtAddr, size: PageSize, valid: PvalidateOp) -> Result<
 
     let changed = cf == 0;
 
-    match ret {
-        0 if changed => Ok(()),
-        0 if !changed => Err(SevSnpError::FAIL_UNCHANGED(0x10).into()),
-        1 => Err(SevSnpError::FAIL_INPUT(ret).into()),
-        6 => Err(SevSnpError::FAIL_SIZEMISMATCH(ret).into()),
-        _ => {
+    rmp_insn_result(ret).map_err(|err| {
+        if let SevSnpError::FAIL_UNKNOWN(_) = err {
             log::error!("PVALIDATE: unexpected return value: {}", ret);
-            unreachable!();
         }
+        err
+    })?;
+
+    if !changed {
+        return Err(SevSnpError::FAIL_UNCHANGED(0x10).into());
     }
+
+    Ok(())
 }
 
 /// Executes the vmmcall instruction.
@@ -189,6 +211,11 @@ bitflags::bitflags! {
 }
 
 pub fn rmp_adjust(addr: VirtAddr, flags: RMPFlags, size: PageSize) -> Result<(), SvsmError> {
+    debug_assert!(
+        addr.is_aligned(size.into()),
+        "Misaligned RMPADJUST at {:#x}",
+        addr
+    );
     let rcx: u64 = match size {
         PageSize::Regular => 0,
         PageSize::Huge => 1,
@@ -218,16 +245,12 @@ pub fn rmp_adjust(addr: VirtAddr, flags: RMPFlags, size: PageSize) -> Result<(),
         return Err(SevSnpError::FAIL_INPUT(1).into());
     }
 
-    match ret {
-        0 => Ok(()),
-        1 => Err(SevSnpError::FAIL_INPUT(ret).into()),
-        2 => Err(SevSnpError::FAIL_PERMISSION(ret).into()),
-        6 => Err(SevSnpError::FAIL_SIZEMISMATCH(ret).into()),
-        _ => {
+    rmp_insn_result(ret).map_err(|err| {
+        if let SevSnpError::FAIL_UNKNOWN(_) = err {
             log::error!("RMPADJUST: Unexpected return value: {:#x}", ret);
-            unreachable!();
         }
-    }
+        err.into()
+    })
 }
 
 pub fn rmp_revoke_guest_access(vaddr: VirtAddr, size: PageSize) -> Result<(), SvsmError> {
@@ -255,3 +278,18 @@ pub fn rmp_clear_guest_vmsa(vaddr: VirtAddr) -> Result<(), SvsmError> {
     rmp_revoke_guest_access(vaddr, PageSize::Regular)?;
     rmp_grant_guest_access(vaddr, PageSize::Regular)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rmp_insn_result() {
+        assert_eq!(rmp_insn_result(0), Ok(()));
+        assert_eq!(rmp_insn_result(1), Err(SevSnpError::FAIL_INPUT(1)));
+        assert_eq!(rmp_insn_result(2), Err(SevSnpError::FAIL_PERMISSION(2)));
+        assert_eq!(rmp_insn_result(6), Err(SevSnpError::FAIL_SIZEMISMATCH(6)));
+        assert_eq!(rmp_insn_result(3), Err(SevSnpError::FAIL_UNKNOWN(3)));
+        assert_eq!(SevSnpError::FAIL_UNKNOWN(3).ret(), 3);
+    }
+}
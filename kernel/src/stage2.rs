@@ -18,7 +18,7 @@ use core::slice;
 use cpuarch::snp_cpuid::SnpCpuidTable;
 use svsm::address::{Address, PhysAddr, VirtAddr};
 use svsm::config::SvsmConfig;
-use svsm::console::{init_console, install_console_logger};
+use svsm::console::{console_flush, init_console, install_console_logger};
 use svsm::cpu::cpuid::{dump_cpuid_table, register_cpuid_table};
 use svsm::cpu::gdt;
 use svsm::cpu::idt::stage2::{early_idt_init, early_idt_init_no_ghcb};
@@ -383,6 +383,7 @@ pub extern "C" fn stage2_main(launch_info: &Stage2LaunchInfo) {
 #[panic_handler]
 fn panic(info: &PanicInfo<'_>) -> ! {
     log::error!("Panic: {}", info);
+    console_flush();
     loop {
         halt();
     }
@@ -7,8 +7,11 @@
 #![cfg_attr(not(test), no_std)]
 #![cfg_attr(not(test), no_main)]
 
+extern crate alloc;
+
 use svsm::fw_meta::{print_fw_meta, validate_fw_memory, SevFWMetaData};
 
+use alloc::vec::Vec;
 use bootlib::kernel_launch::KernelLaunchInfo;
 use core::arch::global_asm;
 use core::mem::size_of;
@@ -16,17 +19,23 @@ use core::panic::PanicInfo;
 use core::ptr;
 use core::slice;
 use cpuarch::snp_cpuid::SnpCpuidTable;
+use svsm::acpi::tables::ACPICPUInfo;
 use svsm::address::{PhysAddr, VirtAddr};
+use svsm::boot::measure::extend_boot_measurement;
 use svsm::config::SvsmConfig;
-use svsm::console::{init_console, install_console_logger};
+use svsm::console::log_filter::log_force_trace;
+use svsm::console::{console_flush, init_console, install_console_logger};
 use svsm::cpu::control_regs::{cr0_init, cr4_init};
 use svsm::cpu::cpuid::{dump_cpuid_table, register_cpuid_table};
 use svsm::cpu::efer::efer_init;
 use svsm::cpu::gdt;
 use svsm::cpu::ghcb::current_ghcb;
+use svsm::cpu::ghcb::hv_features::{hv_features, init_hv_features};
 use svsm::cpu::idt::svsm::{early_idt_init, idt_init};
 use svsm::cpu::percpu::PerCpu;
-use svsm::cpu::percpu::{this_cpu, this_cpu_mut, this_cpu_shared, this_cpu_unsafe};
+use svsm::cpu::percpu::{
+    current_task_name, this_cpu, this_cpu_mut, this_cpu_shared, this_cpu_unsafe,
+};
 use svsm::cpu::smp::start_secondary_cpus;
 use svsm::debug::gdbstub::svsm_gdbstub::{debug_break, gdbstub_start};
 use svsm::debug::stacktrace::print_stack;
@@ -34,6 +43,7 @@ use svsm::error::SvsmError;
 use svsm::fs::{initialize_fs, populate_ram_fs};
 use svsm::fw_cfg::FwCfg;
 use svsm::greq::driver::guest_request_driver_init;
+use svsm::greq::services::check_boot_tcb;
 use svsm::igvm_params::IgvmParams;
 use svsm::kernel_region::new_kernel_region;
 use svsm::mm::alloc::{memory_info, print_memory_info, root_mem_init};
@@ -42,10 +52,11 @@ use svsm::mm::pagetable::paging_init;
 use svsm::mm::virtualrange::virt_log_usage;
 use svsm::mm::{init_kernel_mapping_info, PerCPUPageMappingGuard};
 use svsm::platform::{SvsmPlatformCell, SVSM_PLATFORM};
+use svsm::protocols::policy::init_security_policy;
 use svsm::requests::{request_loop, request_processing_main, update_mappings};
 use svsm::serial::SerialPort;
 use svsm::sev::utils::{rmp_adjust, RMPFlags};
-use svsm::sev::{init_hypervisor_ghcb_features, secrets_page, secrets_page_mut, sev_status_init};
+use svsm::sev::{secrets_page, secrets_page_mut, sev_status_init};
 use svsm::svsm_console::SVSMIOPort;
 use svsm::svsm_paging::{init_page_table, invalidate_early_boot_memory};
 use svsm::task::exec_user;
@@ -216,7 +227,7 @@ fn validate_fw(config: &SvsmConfig<'_>, launch_info: &KernelLaunchInfo) -> Resul
                 RMPFlags::GUEST_VMPL | RMPFlags::RWX,
                 PageSize::Regular,
             ) {
-                log::info!("rmpadjust failed for addr {:#018x}", vaddr);
+                log::info!("rmpadjust failed for addr {}", vaddr);
                 return Err(e);
             }
         }
@@ -225,6 +236,28 @@ fn validate_fw(config: &SvsmConfig<'_>, launch_info: &KernelLaunchInfo) -> Resul
     Ok(())
 }
 
+fn measure_memory_map(config: &SvsmConfig<'_>) -> Result<(), SvsmError> {
+    let regions = config.get_memory_regions()?;
+    let data: Vec<u8> = regions
+        .iter()
+        .flat_map(|r| [u64::from(r.start()), u64::from(r.end())])
+        .flat_map(u64::to_le_bytes)
+        .collect();
+    extend_boot_measurement("memory-map", &data);
+    Ok(())
+}
+
+fn measure_cpu_info(cpus: &[ACPICPUInfo]) {
+    let data: Vec<u8> = cpus
+        .iter()
+        .flat_map(|cpu| {
+            let [a, b, c, d] = cpu.apic_id.to_le_bytes();
+            [a, b, c, d, u8::from(cpu.enabled)]
+        })
+        .collect();
+    extend_boot_measurement("acpi-cpus", &data);
+}
+
 pub fn memory_init(launch_info: &KernelLaunchInfo) {
     root_mem_init(
         PhysAddr::from(launch_info.heap_area_phys_start),
@@ -241,7 +274,7 @@ pub fn boot_stack_info() {
     // static, but we're simply printing its address. We are not creating a
     // reference so this is safe.
     let vaddr = unsafe { VirtAddr::from(ptr::addr_of!(bsp_stack_end)) };
-    log::info!("Boot stack starts        @ {:#018x}", vaddr);
+    log::info!("Boot stack starts        @ {}", vaddr);
 }
 
 fn mapping_info_init(launch_info: &KernelLaunchInfo) {
@@ -252,17 +285,18 @@ fn mapping_info_init(launch_info: &KernelLaunchInfo) {
     );
 }
 
+/// Fails with [`SvsmError::InvalidAddress`] if the provided address is not
+/// aligned to a [`SnpCpuidTable`].
+///
 /// # Panics
 ///
-/// Panics if the provided address is not aligned to a [`SnpCpuidTable`].
-fn init_cpuid_table(addr: VirtAddr) {
+/// Panics if the CPUID table has already been initialized.
+fn init_cpuid_table(addr: VirtAddr) -> Result<(), SvsmError> {
     // SAFETY: this is called from the main function for the SVSM and no other
     // CPUs have been brought up, so the pointer cannot be aliased.
-    // `aligned_mut()` will check alignment for us.
-    let table = unsafe {
-        addr.aligned_mut::<SnpCpuidTable>()
-            .expect("Misaligned SNP CPUID table address")
-    };
+    // `as_aligned_mut()` will check alignment for us.
+    let table =
+        unsafe { addr.as_aligned_mut::<SnpCpuidTable>() }.ok_or(SvsmError::InvalidAddress)?;
 
     for func in table.func.iter_mut().take(table.count as usize) {
         if func.eax_in == 0x8000001f {
@@ -274,6 +308,7 @@ fn init_cpuid_table(addr: VirtAddr) {
         .init(table)
         .expect("Already initialized CPUID page");
     register_cpuid_table(&CPUID_PAGE);
+    Ok(())
 }
 
 #[no_mangle]
@@ -299,7 +334,8 @@ pub extern "C" fn svsm_start(li: &KernelLaunchInfo, vb_addr: usize) {
     let mut platform_cell = SvsmPlatformCell::new(li.platform_type);
     let platform = platform_cell.as_mut_dyn_ref();
 
-    init_cpuid_table(VirtAddr::from(launch_info.cpuid_page));
+    init_cpuid_table(VirtAddr::from(launch_info.cpuid_page))
+        .expect("Misaligned SNP CPUID table address");
     dump_cpuid_table();
 
     let secrets_page_virt = VirtAddr::from(launch_info.secrets_page);
@@ -373,7 +409,7 @@ pub extern "C" fn svsm_start(li: &KernelLaunchInfo, vb_addr: usize) {
         cpu_unsafe.get_top_of_stack()
     };
 
-    log::info!("BSP Runtime stack starts @ {:#018x}", bp);
+    log::info!("BSP Runtime stack starts @ {}", bp);
 
     SVSM_PLATFORM
         .init(&platform_cell)
@@ -395,7 +431,8 @@ pub extern "C" fn svsm_main() {
     // a remote GDB connection
     //debug_break();
 
-    init_hypervisor_ghcb_features().expect("Failed to obtain hypervisor GHCB features");
+    init_hv_features().expect("Failed to obtain hypervisor GHCB features");
+    extend_boot_measurement("hv-features", &hv_features().flags().bits().to_le_bytes());
 
     this_cpu()
         .configure_hv_doorbell()
@@ -413,7 +450,10 @@ pub extern "C" fn svsm_main() {
         SvsmConfig::FirmwareConfig(FwCfg::new(&CONSOLE_IO))
     };
 
+    init_security_policy(config.service_policy());
+
     init_memory_map(&config, &LAUNCH_INFO).expect("Failed to init guest memory map");
+    measure_memory_map(&config).expect("Failed to get memory regions");
 
     initialize_fs();
 
@@ -424,6 +464,7 @@ pub extern "C" fn svsm_main() {
         .expect("Failed to invalidate early boot memory");
 
     let cpus = config.load_cpu_info().expect("Failed to load ACPI tables");
+    measure_cpu_info(&cpus);
     let mut nr_cpus = 0;
 
     for cpu in cpus.iter() {
@@ -444,14 +485,18 @@ pub extern "C" fn svsm_main() {
         validate_fw(&config, &LAUNCH_INFO).expect("Failed to validate flash memory");
     }
 
-    guest_request_driver_init();
+    guest_request_driver_init().expect("SnpGuestRequestDriver failed to initialize");
+    check_boot_tcb();
 
     if let Some(ref fw_meta) = fw_metadata {
         prepare_fw_launch(fw_meta).expect("Failed to setup guest VMSA/CAA");
     }
 
     #[cfg(all(feature = "mstpm", not(test)))]
-    vtpm_init().expect("vTPM failed to initialize");
+    {
+        vtpm_init().expect("vTPM failed to initialize");
+        extend_boot_measurement("vtpm", &[]);
+    }
 
     virt_log_usage();
 
@@ -461,7 +506,8 @@ pub extern "C" fn svsm_main() {
         }
     }
 
-    create_kernel_task(request_processing_main).expect("Failed to launch request processing task");
+    create_kernel_task(request_processing_main, "request-processing")
+        .expect("Failed to launch request processing task");
 
     #[cfg(test)]
     crate::test_main();
@@ -482,9 +528,16 @@ fn panic(info: &PanicInfo<'_>) -> ! {
     secrets_page_mut().clear_vmpck(2);
     secrets_page_mut().clear_vmpck(3);
 
-    log::error!("Panic: CPU[{}] {}", this_cpu().get_apic_id(), info);
+    log_force_trace();
+    log::error!(
+        "Panic: CPU[{}] in task '{}': {}",
+        this_cpu().get_apic_id(),
+        current_task_name(),
+        info
+    );
 
     print_stack(3);
+    console_flush();
 
     loop {
         debug_break();
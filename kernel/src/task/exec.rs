@@ -27,7 +27,7 @@ fn convert_elf_phdr_flags(flags: Elf64PhdrFlags) -> VMFileMappingFlags {
     vm_flags
 }
 
-pub fn exec_user(binary: &str) -> Result<(), SvsmError> {
+pub fn exec_user(binary: &'static str) -> Result<(), SvsmError> {
     let fh = open(binary)?;
     let file_size = fh.size();
 
@@ -46,7 +46,7 @@ pub fn exec_user(binary: &str) -> Result<(), SvsmError> {
     let virt_base = alloc_info.range.vaddr_begin;
     let entry = elf_bin.get_entry(virt_base);
 
-    let task = create_user_task(entry.try_into().unwrap())?;
+    let task = create_user_task(entry.try_into().unwrap(), binary)?;
 
     for seg in elf_bin.image_load_segment_iter(virt_base) {
         let virt_start = VirtAddr::from(seg.vaddr_range.vaddr_begin);
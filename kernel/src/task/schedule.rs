@@ -223,9 +223,12 @@ impl TaskList {
 
 pub static TASKLIST: SpinLock<TaskList> = SpinLock::new(TaskList::new());
 
-pub fn create_kernel_task(entry: extern "C" fn()) -> Result<TaskPointer, SvsmError> {
+pub fn create_kernel_task(
+    entry: extern "C" fn(),
+    name: &'static str,
+) -> Result<TaskPointer, SvsmError> {
     let mut cpu = this_cpu_mut();
-    let task = Task::create(&mut cpu, entry)?;
+    let task = Task::create(&mut cpu, entry, name)?;
     TASKLIST.lock().list().push_back(task.clone());
 
     // Put task on the runqueue of this CPU
@@ -237,9 +240,9 @@ pub fn create_kernel_task(entry: extern "C" fn()) -> Result<TaskPointer, SvsmErr
     Ok(task)
 }
 
-pub fn create_user_task(user_entry: usize) -> Result<TaskPointer, SvsmError> {
+pub fn create_user_task(user_entry: usize, name: &'static str) -> Result<TaskPointer, SvsmError> {
     let mut cpu = this_cpu_mut();
-    let task = Task::create_user(&mut cpu, user_entry)?;
+    let task = Task::create_user(&mut cpu, user_entry, name)?;
     TASKLIST.lock().list().push_back(task.clone());
 
     // Put task on the runqueue of this CPU
@@ -133,6 +133,9 @@ pub struct Task {
     /// ID of the task
     id: u32,
 
+    /// Name of the task, used in diagnostic messages
+    name: &'static str,
+
     /// Link to global task list
     list_link: LinkedListAtomicLink,
 
@@ -163,12 +166,17 @@ impl fmt::Debug for Task {
             .field("rsp", &self.rsp)
             .field("state", &self.sched_state.lock_read().state)
             .field("id", &self.id)
+            .field("name", &self.name)
             .finish()
     }
 }
 
 impl Task {
-    pub fn create(cpu: &mut PerCpu, entry: extern "C" fn()) -> Result<TaskPointer, SvsmError> {
+    pub fn create(
+        cpu: &mut PerCpu,
+        entry: extern "C" fn(),
+        name: &'static str,
+    ) -> Result<TaskPointer, SvsmError> {
         let mut pgtable = cpu.get_pgtable().clone_shared()?;
 
         cpu.populate_page_table(&mut pgtable);
@@ -203,12 +211,17 @@ impl Task {
                 cpu: cpu.get_apic_id(),
             }),
             id: TASK_ID_ALLOCATOR.next_id(),
+            name,
             list_link: LinkedListAtomicLink::default(),
             runlist_link: LinkedListAtomicLink::default(),
         }))
     }
 
-    pub fn create_user(cpu: &mut PerCpu, user_entry: usize) -> Result<TaskPointer, SvsmError> {
+    pub fn create_user(
+        cpu: &mut PerCpu,
+        user_entry: usize,
+        name: &'static str,
+    ) -> Result<TaskPointer, SvsmError> {
         let mut pgtable = cpu.get_pgtable().clone_shared()?;
 
         cpu.populate_page_table(&mut pgtable);
@@ -246,6 +259,7 @@ impl Task {
                 cpu: cpu.get_apic_id(),
             }),
             id: TASK_ID_ALLOCATOR.next_id(),
+            name,
             list_link: LinkedListAtomicLink::default(),
             runlist_link: LinkedListAtomicLink::default(),
         }))
@@ -259,6 +273,10 @@ impl Task {
         self.id
     }
 
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
     pub fn set_task_running(&self) {
         self.sched_state.lock_write().state = TaskState::RUNNING;
     }
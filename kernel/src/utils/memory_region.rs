@@ -4,8 +4,8 @@
 //
 // Author: Carlos López <carlos.lopez@suse.com>
 
-use crate::address::Address;
-use crate::types::PageSize;
+use crate::address::{Address, PhysAddr, VirtAddr};
+use crate::types::{PageSize, PAGE_SIZE};
 
 /// An abstraction over a memory region, expressed in terms of physical
 /// ([`PhysAddr`](crate::address::PhysAddr)) or virtual
@@ -147,6 +147,46 @@ where
         self.start() <= other.end() && self.end() >= other.start()
     }
 
+    /// Checks whether two regions touch without overlapping, i.e. whether
+    /// one region ends exactly where the other one starts.
+    ///
+    /// ```rust
+    /// # use svsm::address::VirtAddr;
+    /// # use svsm::types::PAGE_SIZE;
+    /// # use svsm::utils::MemoryRegion;
+    /// let r1 = MemoryRegion::new(VirtAddr::from(0xffffff0000u64), PAGE_SIZE);
+    /// let r2 = MemoryRegion::new(VirtAddr::from(0xffffff1000u64), PAGE_SIZE);
+    /// assert!(r1.is_adjacent(&r2));
+    /// assert!(r2.is_adjacent(&r1));
+    ///
+    /// let r3 = MemoryRegion::new(VirtAddr::from(0xffffff0000u64), PAGE_SIZE * 2);
+    /// assert!(!r3.is_adjacent(&r2));
+    /// ```
+    pub fn is_adjacent(&self, other: &Self) -> bool {
+        self.end() == other.start() || other.end() == self.start()
+    }
+
+    /// Merge two regions if they are contiguous or overlapping, see
+    /// [`MemoryRegion::contiguous()`]. Unlike [`MemoryRegion::merge()`],
+    /// this never covers memory outside of both regions.
+    ///
+    /// ```rust
+    /// # use svsm::address::VirtAddr;
+    /// # use svsm::types::PAGE_SIZE;
+    /// # use svsm::utils::MemoryRegion;
+    /// let r1 = MemoryRegion::new(VirtAddr::from(0xffffff0000u64), PAGE_SIZE);
+    /// let r2 = MemoryRegion::new(VirtAddr::from(0xffffff1000u64), PAGE_SIZE);
+    /// let r3 = r1.try_merge(&r2).unwrap();
+    /// assert_eq!(r3.start(), r1.start());
+    /// assert_eq!(r3.end(), r2.end());
+    ///
+    /// let r4 = MemoryRegion::new(VirtAddr::from(0xffffff3000u64), PAGE_SIZE);
+    /// assert!(r1.try_merge(&r4).is_none());
+    /// ```
+    pub fn try_merge(&self, other: &Self) -> Option<Self> {
+        self.contiguous(other).then(|| self.merge(other))
+    }
+
     /// Merge two regions. It does not check whether the two regions are
     /// contiguous in the first place, so the resulting region will cover
     /// any non-overlapping memory between both.
@@ -189,6 +229,60 @@ where
             .map(A::from)
     }
 
+    /// Split the memory region into non-overlapping chunks of `page_size`
+    /// bytes, each aligned to `page_size`. Chunks start at the first
+    /// `page_size`-aligned address within the region. If `partial` is `true`,
+    /// a trailing chunk shorter than `page_size` is returned as-is, otherwise
+    /// it is discarded.
+    ///
+    /// ```rust
+    /// # use svsm::address::VirtAddr;
+    /// # use svsm::types::PAGE_SIZE;
+    /// # use svsm::utils::MemoryRegion;
+    /// let region = MemoryRegion::new(VirtAddr::from(0xffffff0000u64), PAGE_SIZE * 2 + 16);
+    /// let mut iter = region.page_chunks(PAGE_SIZE, false);
+    /// assert_eq!(iter.next().map(|r| r.start()), Some(VirtAddr::from(0xffffff0000u64)));
+    /// assert_eq!(iter.next().map(|r| r.start()), Some(VirtAddr::from(0xffffff1000u64)));
+    /// assert!(iter.next().is_none());
+    ///
+    /// let last = region.page_chunks(PAGE_SIZE, true).last().unwrap();
+    /// assert_eq!(last.start(), VirtAddr::from(0xffffff2000u64));
+    /// assert_eq!(last.len(), 16);
+    /// ```
+    pub fn page_chunks(&self, page_size: usize, partial: bool) -> impl Iterator<Item = Self> {
+        let end = self.end();
+        let first = self.start().align_up(page_size);
+        (first.bits()..end.bits())
+            .step_by(page_size)
+            .map(A::from)
+            .filter_map(move |start| match start.checked_add(page_size) {
+                Some(chunk_end) if chunk_end <= end => Some(Self {
+                    start,
+                    end: chunk_end,
+                }),
+                _ => partial.then_some(Self { start, end }),
+            })
+    }
+
+    /// Iterate over the start addresses of all complete 4KiB pages within
+    /// the memory region. This is a shorthand for
+    /// [`MemoryRegion::page_chunks()`] with a size of [`PAGE_SIZE`].
+    ///
+    /// ```rust
+    /// # use svsm::address::VirtAddr;
+    /// # use svsm::types::PAGE_SIZE;
+    /// # use svsm::utils::MemoryRegion;
+    /// let region = MemoryRegion::new(VirtAddr::from(0xffffff0000u64), PAGE_SIZE * 2);
+    /// let mut iter = region.pages_4k();
+    /// assert_eq!(iter.next(), Some(VirtAddr::from(0xffffff0000u64)));
+    /// assert_eq!(iter.next(), Some(VirtAddr::from(0xffffff1000u64)));
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    pub fn pages_4k(&self) -> impl Iterator<Item = A> {
+        self.page_chunks(PAGE_SIZE, false)
+            .map(|chunk| chunk.start())
+    }
+
     /// Check whether an address is within this region.
     ///
     /// ```rust
@@ -222,3 +316,72 @@ where
         self.start() <= other.start() && other.end() <= self.end()
     }
 }
+
+impl MemoryRegion<VirtAddr> {
+    /// Create a memory region from a pair of pointers, typically the start
+    /// and end symbols of a section defined in the linker script.
+    ///
+    /// ```rust
+    /// # use svsm::utils::MemoryRegion;
+    /// let buf = [0u8; 16];
+    /// let range = buf.as_ptr_range();
+    /// let region = MemoryRegion::from_kernel_symbols(range.start, range.end).unwrap();
+    /// assert_eq!(region.len(), 16);
+    /// assert!(MemoryRegion::from_kernel_symbols(range.end, range.start).is_none());
+    /// ```
+    pub fn from_kernel_symbols(start: *const u8, end: *const u8) -> Option<Self> {
+        let start = VirtAddr::from(start);
+        let end = VirtAddr::from(end);
+        (start <= end).then_some(Self::from_addresses(start, end))
+    }
+
+    /// Translates the region out of the linear mapping which maps
+    /// `phys_base` at `virt_base`. Returns `None` if the region starts
+    /// below `virt_base` or the translated region would overflow.
+    ///
+    /// ```rust
+    /// # use svsm::address::{PhysAddr, VirtAddr};
+    /// # use svsm::utils::MemoryRegion;
+    /// let region = MemoryRegion::new(VirtAddr::from(0xffff_8000_0000_2000u64), 0x1000);
+    /// let phys_base = PhysAddr::from(0x1000u64);
+    /// let virt_base = VirtAddr::from(0xffff_8000_0000_0000u64);
+    /// let phys = region.to_phys(phys_base, virt_base).unwrap();
+    /// assert_eq!(phys.start(), PhysAddr::from(0x3000u64));
+    /// assert_eq!(phys.to_virt(phys_base, virt_base).unwrap().start(), region.start());
+    /// ```
+    pub fn to_phys(
+        &self,
+        phys_base: PhysAddr,
+        virt_base: VirtAddr,
+    ) -> Option<MemoryRegion<PhysAddr>> {
+        let offset = self.start().bits().checked_sub(virt_base.bits())?;
+        MemoryRegion::checked_new(phys_base.checked_add(offset)?, self.len())
+    }
+}
+
+impl MemoryRegion<PhysAddr> {
+    /// Translates the region into the linear mapping which maps
+    /// `phys_base` at `virt_base`. Returns `None` if the region starts
+    /// below `phys_base` or the translated region would overflow.
+    ///
+    /// ```rust
+    /// # use svsm::address::{PhysAddr, VirtAddr};
+    /// # use svsm::utils::MemoryRegion;
+    /// let region = MemoryRegion::new(PhysAddr::from(0x3000u64), 0x1000);
+    /// let phys_base = PhysAddr::from(0x1000u64);
+    /// let virt_base = VirtAddr::from(0xffff_8000_0000_0000u64);
+    /// let virt = region.to_virt(phys_base, virt_base).unwrap();
+    /// assert_eq!(virt.start(), VirtAddr::from(0xffff_8000_0000_2000u64));
+    /// assert_eq!(virt.len(), 0x1000);
+    /// assert!(region.to_virt(PhysAddr::from(0x4000u64), virt_base).is_none());
+    /// assert!(region.to_virt(phys_base, VirtAddr::from(u64::MAX)).is_none());
+    /// ```
+    pub fn to_virt(
+        &self,
+        phys_base: PhysAddr,
+        virt_base: VirtAddr,
+    ) -> Option<MemoryRegion<VirtAddr>> {
+        let offset = self.start().bits().checked_sub(phys_base.bits())?;
+        MemoryRegion::checked_new(virt_base.checked_add(offset)?, self.len())
+    }
+}
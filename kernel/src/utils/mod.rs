@@ -7,6 +7,7 @@
 pub mod bitmap_allocator;
 pub mod immut_after_init;
 pub mod memory_region;
+pub mod once;
 pub mod util;
 
 pub use memory_region::MemoryRegion;
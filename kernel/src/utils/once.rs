@@ -0,0 +1,170 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2024 SUSE LLC
+
+use core::cell::UnsafeCell;
+use core::fmt;
+use core::hint::spin_loop;
+use core::mem::MaybeUninit;
+use core::sync::atomic::{AtomicU8, Ordering};
+
+const UNINITIALIZED: u8 = 0;
+const INITIALIZING: u8 = 1;
+const INITIALIZED: u8 = 2;
+
+/// A cell which is written exactly once and can then be read from all CPUs.
+///
+/// Unlike [`ImmutAfterInitCell`](super::immut_after_init::ImmutAfterInitCell)
+/// a `Once` is safe to initialize concurrently, so neither initialization
+/// nor access require `unsafe{}` at the call site.
+///
+/// # Examples
+/// ```
+/// # use svsm::utils::once::Once;
+/// static X: Once<u64> = Once::new();
+/// pub fn main() {
+///     assert_eq!(X.try_get(), None);
+///     X.init(123);
+///     assert_eq!(*X.get(), 123);
+///     assert_eq!(*X.get_or_init(|| 456), 123);
+/// }
+/// ```
+pub struct Once<T> {
+    state: AtomicU8,
+    data: UnsafeCell<MaybeUninit<T>>,
+}
+
+// SAFETY: the value is only written while the state is INITIALIZING, which
+// a single CPU can enter, and only shared after it is INITIALIZED.
+unsafe impl<T: Send + Sync> Sync for Once<T> {}
+unsafe impl<T: Send> Send for Once<T> {}
+
+impl<T> Once<T> {
+    pub const fn new() -> Self {
+        Self {
+            state: AtomicU8::new(UNINITIALIZED),
+            data: UnsafeCell::new(MaybeUninit::uninit()),
+        }
+    }
+
+    /// Attempts to move the cell into the INITIALIZING state and, if that
+    /// succeeds, stores the value returned by `f`.
+    ///
+    /// # Returns
+    ///
+    /// `false` if the cell was already being initialized.
+    fn try_init_with<F: FnOnce() -> T>(&self, f: F) -> bool {
+        if self
+            .state
+            .compare_exchange(
+                UNINITIALIZED,
+                INITIALIZING,
+                Ordering::Acquire,
+                Ordering::Acquire,
+            )
+            .is_err()
+        {
+            return false;
+        }
+        // SAFETY: the successful transition to INITIALIZING grants
+        // exclusive access to the data.
+        unsafe { (*self.data.get()).write(f()) };
+        self.state.store(INITIALIZED, Ordering::Release);
+        true
+    }
+
+    /// Initializes the cell with `value`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the cell was initialized before.
+    pub fn init(&self, value: T) {
+        if !self.try_init_with(|| value) {
+            panic!("Once cell initialized twice");
+        }
+    }
+
+    /// Returns the value, or `None` if the cell was not initialized yet.
+    pub fn try_get(&self) -> Option<&T> {
+        if self.state.load(Ordering::Acquire) == INITIALIZED {
+            // SAFETY: the value was written before the state became
+            // INITIALIZED and is never modified afterwards.
+            Some(unsafe { (*self.data.get()).assume_init_ref() })
+        } else {
+            None
+        }
+    }
+
+    /// Returns the value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the cell was not initialized yet.
+    pub fn get(&self) -> &T {
+        self.try_get()
+            .expect("Once cell used before initialization")
+    }
+
+    /// Returns the value, initializing the cell with the result of `f` if
+    /// needed. If another CPU is initializing the cell at the same time,
+    /// waits for it to finish.
+    pub fn get_or_init<F: FnOnce() -> T>(&self, f: F) -> &T {
+        self.try_init_with(f);
+        loop {
+            if let Some(value) = self.try_get() {
+                return value;
+            }
+            spin_loop();
+        }
+    }
+}
+
+impl<T> Default for Once<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Once<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Once").field(&self.try_get()).finish()
+    }
+}
+
+impl<T> Drop for Once<T> {
+    fn drop(&mut self) {
+        if *self.state.get_mut() == INITIALIZED {
+            // SAFETY: the value was initialized and is not used anymore.
+            unsafe { self.data.get_mut().assume_init_drop() };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_once_init() {
+        let once = Once::new();
+        assert_eq!(once.try_get(), None);
+        once.init(1u32);
+        assert_eq!(*once.get(), 1);
+        assert_eq!(*once.get_or_init(|| 2), 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_once_init_twice() {
+        let once = Once::new();
+        once.init(1u32);
+        once.init(2u32);
+    }
+
+    #[test]
+    fn test_once_get_or_init() {
+        let once = Once::new();
+        assert_eq!(*once.get_or_init(|| 2u32), 2);
+        assert_eq!(once.try_get(), Some(&2));
+    }
+}